@@ -0,0 +1,184 @@
+//! Sampling the counts of won, lost and voided games directly from their
+//! exact distribution, for when only the [Results] are wanted.
+//!
+//! The games of a strategy are independent and equally distributed, so the
+//! number of voided games is binomially distributed with the exact void rate,
+//! and the number of wins among the rest with the exact win rate. Drawing
+//! those two numbers takes the same time for a thousand games as for a
+//! quintillion. The results are distributed like the results of playing the
+//! games, up to the precision of floating point numbers, but no games are
+//! played: they are marked as [aggregate](crate::RunMetadata::is_aggregate),
+//! and only strategies with an [exact_win_rate] can be sampled.
+//!
+//! ```rust
+//! use monty_pyrs::{GameConfig, Host, MontyHall, Player};
+//!
+//! let config = GameConfig::new(5, 2).unwrap().with_host(Host::MontyFall);
+//! let players = [Player::AlwaysSwitch, Player::AlwaysStay, Player::RandomSwitch(0.3)];
+//! let results = MontyHall::default().play_aggregate(&config, &players, u64::MAX).unwrap();
+//! assert_eq!(results.total_games(), u64::MAX);
+//! assert!(results.metadata()[0].is_aggregate());
+//! for (strategy, deviation) in results.deviations() {
+//!     assert!(deviation.unwrap().abs() < 1e-6, "{}", strategy);
+//! }
+//!
+//! // Small runs vary as much as played ones: the wins of always switching in
+//! // n games have mean 2n/3 and variance 2n/9
+//! let config = GameConfig::default();
+//! let mut monty = MontyHall::seeded(3);
+//! for games in [12, 300, 1_000_000] {
+//!     let wins: Vec<f64> = (0..20_000)
+//!         .map(|_| monty.play_aggregate(&config, &players[..1], games).unwrap())
+//!         .map(|results| results.result_set("always_switch").unwrap().wins() as f64)
+//!         .collect();
+//!     let mean = wins.iter().sum::<f64>() / wins.len() as f64;
+//!     let variance = wins.iter().map(|won| (won - mean).powi(2)).sum::<f64>() / wins.len() as f64;
+//!     let games = games as f64;
+//!     assert!((mean / games - 2. / 3.).abs() < 0.005, "{} games", games);
+//!     assert!((variance / (games * 2. / 9.) - 1.).abs() < 0.05, "{} games", games);
+//! }
+//!
+//! let player = [Player::SwitchIfHostOpened(1)];
+//! assert!(MontyHall::default().play_aggregate(&config, &player, 1000).is_err());
+//! ```
+
+use crate::stats::ln_gamma;
+use crate::{
+    exact_void_rate, exact_win_rate, GameConfig, MontyError, Player, PlayerStrategy, ResultSet,
+    Results,
+};
+use num_traits::ToPrimitive;
+use rand_core::RngCore;
+
+/// A uniformly distributed float in [0, 1), from the top 53 bits of a word.
+fn uniform<R: RngCore + ?Sized>(rng: &mut R) -> f64 {
+    (rng.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// The natural logarithm of `a! / b!`.
+///
+/// The factorials of large numbers have logarithms too large to subtract
+/// without losing all precision, so the difference is taken inside Stirling's
+/// approximation instead.
+fn ln_factorial_ratio(a: u64, b: u64) -> f64 {
+    if a < 16 || b < 16 {
+        return ln_gamma(a as f64 + 1.) - ln_gamma(b as f64 + 1.);
+    }
+    // ln x! = (x + 1/2) ln x - x + ln(2 pi) / 2 + correction(x)
+    let correction = |x: f64| {
+        let squared = x * x;
+        (1. / 12. - (1. / 360. - 1. / (1260. * squared)) / squared) / x
+    };
+    let (a, b) = (a as f64, b as f64);
+    let difference = a - b;
+    (b + 0.5) * (difference / b).ln_1p() + difference * a.ln() - difference + correction(a)
+        - correction(b)
+}
+
+/// Draw the number of successes in `n` trials that each succeed with
+/// probability `p`.
+///
+/// Small means are drawn by inverting the distribution function, larger ones
+/// with Hörmann's transformed rejection with squeeze (BTRS), which takes a
+/// constant expected number of draws.
+pub(crate) fn binomial<R: RngCore + ?Sized>(rng: &mut R, n: u64, p: f64) -> u64 {
+    if n == 0 || p <= 0. {
+        return 0;
+    }
+    if p >= 1. {
+        return n;
+    }
+    if p > 0.5 {
+        return n - binomial(rng, n, 1. - p);
+    }
+    let q = 1. - p;
+    let mean = n as f64 * p;
+    if mean < 10. {
+        // Walk up the distribution function until it passes a uniform draw.
+        // The mean is small, so the walk is short
+        let ratio = p / q;
+        let first = (n as f64 * (-p).ln_1p()).exp();
+        loop {
+            let mut draw = uniform(rng);
+            let mut probability = first;
+            let mut successes = 0;
+            // Anything further out is less likely than the rounding errors
+            while successes <= 110.min(n) {
+                if draw < probability {
+                    return successes;
+                }
+                draw -= probability;
+                successes += 1;
+                probability *= ((n - successes + 1) as f64 / successes as f64) * ratio;
+            }
+        }
+    }
+    let spread = (mean * q).sqrt();
+    let b = 1.15 + 2.53 * spread;
+    let a = -0.0873 + 0.0248 * b + 0.01 * p;
+    let c = mean + 0.5;
+    let alpha = (2.83 + 5.1 / b) * spread;
+    let squeeze = 0.92 - 4.2 / b;
+    let ln_odds = (p / q).ln();
+    let mode = ((n as f64 + 1.) * p).floor() as u64;
+    loop {
+        let u = uniform(rng) - 0.5;
+        let v = uniform(rng);
+        let us = 0.5 - u.abs();
+        let k = ((2. * a / us + b) * u + c).floor();
+        if !(k >= 0. && k <= n as f64) {
+            continue;
+        }
+        let k = (k as u64).min(n);
+        if us >= 0.07 && v <= squeeze {
+            return k;
+        }
+        // The logarithm of the probability of k relative to the mode
+        let relative = ln_factorial_ratio(mode, k)
+            + ln_factorial_ratio(n - mode, n - k)
+            + (k as f64 - mode as f64) * ln_odds;
+        if (v * alpha / (a / (us * us) + b)).ln() <= relative {
+            return k;
+        }
+    }
+}
+
+/// Sample the results of `games` games of `player`.
+fn sample<R: RngCore + ?Sized>(
+    rng: &mut R,
+    config: &GameConfig,
+    player: &Player,
+    games: u64,
+) -> Result<ResultSet, MontyError> {
+    let rates = exact_win_rate(config, player).zip(exact_void_rate(config));
+    let (win_rate, void_rate) = rates
+        .and_then(|(win_rate, void_rate)| Some((win_rate.to_f64()?, void_rate.to_f64()?)))
+        .ok_or_else(|| MontyError::NoExactDistribution(player.name()))?;
+    let voided = binomial(rng, games, void_rate);
+    let wins = binomial(rng, games - voided, win_rate);
+    Ok(ResultSet {
+        wins,
+        losses: games - voided - wins,
+        voided,
+    })
+}
+
+/// Sample the results of `iterations` games, split between the strategies as in
+/// [MontyHall::play_multiple](crate::MontyHall::play_multiple).
+pub(crate) fn play<R: RngCore + ?Sized>(
+    rng: &mut R,
+    config: &GameConfig,
+    players: &[Player],
+    iterations: u64,
+) -> Result<Results, MontyError> {
+    let mut results = Results::default();
+    let strategies = players.len() as u64;
+    for (position, player) in players.iter().enumerate() {
+        let games =
+            iterations / strategies + u64::from((position as u64) < iterations % strategies);
+        let set = sample(rng, config, player, games)?;
+        let index = results.index_of(player.name());
+        results.strategies[index].1 += set;
+    }
+    Ok(results)
+}
@@ -0,0 +1,108 @@
+//! Exact win probabilities of the built-in strategies, the ground truth to
+//! compare simulated results against.
+//!
+//! Like the win rates in [Results](crate::Results), the probabilities are
+//! conditioned on the game not being voided.
+//!
+//! ```rust
+//! use monty_pyrs::{GameConfig, Host, Player, play_threaded};
+//!
+//! let players = [Player::AlwaysSwitch, Player::AlwaysStay, Player::RandomSwitch(0.25)];
+//! for host in ["knowledgeable", "ignorant", "monty_fall", "monty_crawl", "angelic", "devilish"] {
+//!     for (doors, opened_by_host) in [(3, 1), (4, 1), (5, 3)] {
+//!         let config = GameConfig::new(doors, opened_by_host).unwrap().with_host(host.parse().unwrap());
+//!         let results = play_threaded(&config, &players, 300_000).unwrap();
+//!         for (strategy, deviation) in results.deviations() {
+//!             assert!(deviation.unwrap().abs() < 0.01, "{} with {:?}", strategy, config);
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::{GameConfig, Host, Player};
+use num_rational::BigRational;
+use num_traits::One;
+
+fn ratio(numerator: u64, denominator: u64) -> BigRational {
+    BigRational::new(numerator.into(), denominator.into())
+}
+
+/// The probabilities of winning when always switching and when always staying.
+fn switch_and_stay(config: &GameConfig) -> Option<(BigRational, BigRational)> {
+    let doors = u64::from(config.doors());
+    let opened = u64::from(config.opened_by_host());
+    // The doors left to switch to
+    let closed = doors - 1 - opened;
+    let knowledgeable = (ratio(doors - 1, doors * closed), ratio(1, doors));
+    Some(match config.host() {
+        Host::Knowledgeable | Host::MontyCrawl => knowledgeable,
+        Host::Ignorant => (ratio(1, doors - opened), ratio(1, doors - opened)),
+        Host::MontyFall if opened == 0 => knowledgeable,
+        // Given that the fall revealed a goat, the car is behind one of the
+        // other doors - 1 doors
+        Host::MontyFall => (ratio(doors - 2, (doors - 1) * closed), ratio(1, doors - 1)),
+        Host::Angelic => (knowledgeable.0 + ratio(1, doors), ratio(1, doors)),
+        Host::Devilish => (ratio(0, 1), ratio(1, doors)),
+        Host::Custom(_) => return None,
+    })
+}
+
+/// The exact probability of `player` winning the game described by `config`.
+///
+/// Returns `None` for custom hosts and players, for switch probabilities that
+/// aren't probabilities, and for strategies that depend on which doors were
+/// opened, whose results also depend on the [Fidelity](crate::Fidelity).
+///
+/// ```rust
+/// use monty_pyrs::{exact_win_rate, GameConfig, Host, Player};
+/// use num_rational::BigRational;
+///
+/// let ratio = |numerator: i32, denominator: i32| Some(BigRational::new(numerator.into(), denominator.into()));
+/// let config = GameConfig::default();
+/// assert_eq!(exact_win_rate(&config, &Player::AlwaysSwitch), ratio(2, 3));
+/// assert_eq!(exact_win_rate(&config, &Player::AlwaysStay), ratio(1, 3));
+/// assert_eq!(exact_win_rate(&config, &Player::RandomSwitch(0.5)), ratio(1, 2));
+/// assert_eq!(exact_win_rate(&config, &Player::RandomSwitch(1.5)), None);
+///
+/// let config = GameConfig::new(100, 98).unwrap();
+/// assert_eq!(exact_win_rate(&config, &Player::AlwaysSwitch), ratio(99, 100));
+/// let config = config.with_host(Host::Ignorant);
+/// assert_eq!(exact_win_rate(&config, &Player::AlwaysSwitch), ratio(1, 2));
+/// ```
+pub fn exact_win_rate(config: &GameConfig, player: &Player) -> Option<BigRational> {
+    player.validate().ok()?;
+    let (switch, stay) = switch_and_stay(config)?;
+    match player {
+        Player::AlwaysSwitch => Some(switch),
+        Player::AlwaysStay => Some(stay),
+        // Whether to switch is decided independently of the game, so the
+        // probability is a mix of always switching and always staying. The
+        // probability is converted exactly, eg. 0.1 as the float nearest to it
+        Player::RandomSwitch(probability) => {
+            let probability = BigRational::from_float(*probability)?;
+            Some(&probability * switch + (BigRational::one() - probability) * stay)
+        }
+        Player::SwitchIfHostOpened(_) | Player::Custom(_) => None,
+    }
+}
+
+/// The exact probability of a game described by `config` being voided because
+/// the host revealed the car, or `None` for custom hosts.
+///
+/// ```rust
+/// use monty_pyrs::{exact_void_rate, GameConfig, Host};
+/// use num_rational::BigRational;
+///
+/// let config = GameConfig::new(10, 4).unwrap().with_host(Host::Ignorant);
+/// assert_eq!(exact_void_rate(&config), Some(BigRational::new(2.into(), 5.into())));
+/// ```
+pub fn exact_void_rate(config: &GameConfig) -> Option<BigRational> {
+    let doors = u64::from(config.doors());
+    let opened = u64::from(config.opened_by_host());
+    match config.host() {
+        Host::Ignorant => Some(ratio(opened, doors)),
+        Host::MontyFall if opened > 0 => Some(ratio(1, doors)),
+        Host::Custom(_) => None,
+        _ => Some(ratio(0, 1)),
+    }
+}
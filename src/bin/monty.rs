@@ -0,0 +1,502 @@
+//! Command-line front end for running simulations on machines without Python.
+//!
+//! ```text
+//! monty run -n 1_000_000 --doors 10 --opened 8 -s always_switch -s "random_switch(0.5)"
+//! monty run -n 1_000_000_000_000_000_000 --aggregate
+//! monty sweep --doors 3-10 --opened 1,2 --format csv
+//! monty bench --threads 4
+//! ```
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use monty_pyrs::{
+    csv_field, replay, Backend, Fidelity, GameConfig, Host, MontyError, MontyHall, Player, Results,
+    RngKind, Simulator, Sweep, TraceFormat, TraceWriter,
+};
+use num_traits::ToPrimitive;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::PathBuf;
+use std::process;
+use std::time::Instant;
+
+/// A blazing fast, mostly stupid Monty Hall problem simulator
+#[derive(Parser)]
+#[clap(name = "monty", version)]
+struct Cli {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Play a single game configuration and print the results per strategy
+    Run {
+        /// The total number of doors
+        #[clap(long, default_value_t = 3)]
+        doors: u32,
+        /// The number of doors the host opens
+        #[clap(long, default_value_t = 1)]
+        opened: u32,
+        /// A strategy to play, eg. always_switch, always_stay, random_switch(0.5) or
+        /// switch_if_opened(1). Repeat to play several [default: always_switch and always_stay]
+        #[clap(short = 's', long = "strategy", value_parser)]
+        strategies: Vec<Player>,
+        /// Sample the counts from their exact distribution in constant time
+        /// instead of playing the games, for strategies with a known exact win rate
+        #[clap(long)]
+        aggregate: bool,
+        #[clap(flatten)]
+        rules: Rules,
+        #[clap(flatten)]
+        engine: Engine,
+        #[clap(flatten)]
+        output: Output,
+    },
+    /// Play every combination of door counts, opened doors and switch
+    /// probabilities, skipping combinations where the host can't open that many doors
+    Sweep {
+        /// Door counts to play, eg. `3-10` or `3,5,100`
+        #[clap(long, value_parser = parse_list, default_value = "3")]
+        doors: List,
+        /// Numbers of opened doors to play, eg. `1-8`
+        #[clap(long, value_parser = parse_list, default_value = "1")]
+        opened: List,
+        /// Probabilities of switching to play, eg. `0,0.25,0.5,0.75,1`
+        #[clap(long = "switch", value_parser = parse_probabilities, default_value = "1,0")]
+        switch_probabilities: Probabilities,
+        #[clap(flatten)]
+        rules: Rules,
+        #[clap(flatten)]
+        engine: Engine,
+        #[clap(flatten)]
+        output: Output,
+    },
+    /// Play games one at a time, streaming a record of every game to a file
+    Trace {
+        /// The file to write the records to
+        output: PathBuf,
+        /// The format of the file: csv, ndjson or binary
+        #[clap(long = "trace-format", value_parser, default_value = "binary")]
+        trace_format: TraceFormat,
+        /// The number of games to play
+        #[clap(short = 'n', long, value_parser = parse_count, default_value = "1_000_000")]
+        iterations: u64,
+        /// The total number of doors
+        #[clap(long, default_value_t = 3)]
+        doors: u32,
+        /// The number of doors the host opens
+        #[clap(long, default_value_t = 1)]
+        opened: u32,
+        /// A strategy to play. Repeat to play several [default: always_switch and always_stay]
+        #[clap(short = 's', long = "strategy", value_parser)]
+        strategies: Vec<Player>,
+        /// The host model
+        #[clap(long, value_parser, default_value = "knowledgeable")]
+        host: Host,
+        /// Whether to take the shortcuts of the fast simulation: fast or faithful.
+        /// Defaults to faithful, so that the picked and opened doors vary
+        #[clap(long, value_parser, default_value = "faithful")]
+        fidelity: Fidelity,
+        /// The seed of the random number generator. Traces the same games as
+        /// the other commands with the same seed, fidelity and generator, except
+        /// for fast games of always switching and always staying, which they play in bulk
+        #[clap(long, default_value_t = 0)]
+        seed: u64,
+        /// The random number generator: xorshift, pcg64, chacha8, chacha20, wyrand or splitmix
+        #[clap(long, value_parser, default_value = "xorshift")]
+        rng: RngKind,
+    },
+    /// Add up the games of a trace file, in any of its formats
+    Replay {
+        /// The trace file to read
+        input: PathBuf,
+        #[clap(flatten)]
+        output: Output,
+    },
+    /// Measure how many classic games are played per second
+    Bench {
+        /// The number of games to play
+        #[clap(short = 'n', long, value_parser = parse_count, default_value = "100_000_000")]
+        iterations: u64,
+        /// The number of worker threads [default: the number of logical CPUs]
+        #[clap(long)]
+        threads: Option<usize>,
+        /// The random number generator to measure
+        #[clap(long, value_parser, default_value = "xorshift")]
+        rng: RngKind,
+        /// The backend to measure: auto, scalar, portable, sse2, avx2 or avx512
+        #[clap(long, value_parser, default_value = "auto")]
+        backend: Backend,
+        #[clap(flatten)]
+        output: Output,
+    },
+}
+
+#[derive(Args)]
+struct Rules {
+    /// The host model: knowledgeable, ignorant, monty_fall, monty_crawl, angelic or devilish
+    #[clap(long, value_parser, default_value = "knowledgeable")]
+    host: Host,
+    /// Whether to take the shortcuts of the fast simulation: fast or faithful
+    #[clap(long, value_parser, default_value = "fast")]
+    fidelity: Fidelity,
+}
+
+#[derive(Args)]
+struct Engine {
+    /// The number of games to play per game configuration
+    #[clap(short = 'n', long, value_parser = parse_count, default_value = "10_000_000")]
+    iterations: u64,
+    /// The number of worker threads [default: the number of logical CPUs]
+    #[clap(long)]
+    threads: Option<usize>,
+    /// The seed all random number generators are derived from
+    #[clap(long, default_value_t = 0)]
+    seed: u64,
+    /// The random number generator: xorshift, pcg64, chacha8, chacha20, wyrand or splitmix
+    #[clap(long, value_parser, default_value = "xorshift")]
+    rng: RngKind,
+    /// How the classic strategies are played: auto, scalar, portable, sse2, avx2 or avx512
+    #[clap(long, value_parser, default_value = "auto")]
+    backend: Backend,
+}
+
+impl Engine {
+    fn simulator(&self, fidelity: Fidelity) -> Result<Simulator, MontyError> {
+        let mut builder = Simulator::builder()
+            .seed(self.seed)
+            .fidelity(fidelity)
+            .rng(self.rng)
+            .backend(self.backend);
+        if let Some(threads) = self.threads {
+            builder = builder.threads(threads);
+        }
+        builder.build()
+    }
+}
+
+#[derive(Args)]
+struct Output {
+    /// How to print the results
+    #[clap(long, value_enum, default_value_t = Format::Table)]
+    format: Format,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    /// Aligned columns for reading
+    Table,
+    /// The full results including how they were played, loadable with `Results::from_json`
+    Json,
+    /// One row per strategy
+    Csv,
+}
+
+/// Parse a count such as `1000000` or `1_000_000`.
+fn parse_count(s: &str) -> Result<u64, String> {
+    s.replace('_', "")
+        .parse()
+        .map_err(|_| format!("{} is not a whole number", s))
+}
+
+/// A list of numbers, parsed as one argument so clap doesn't treat it as repeated.
+#[derive(Clone)]
+struct List(Vec<u32>);
+
+/// Parse a comma-separated list of numbers and inclusive ranges such as `3-10`.
+fn parse_list(s: &str) -> Result<List, String> {
+    let mut values = Vec::new();
+    for part in s.split(',') {
+        let parse = |s: &str| {
+            s.trim()
+                .parse::<u32>()
+                .map_err(|_| format!("{} is not a number or a range", part))
+        };
+        match part.split_once('-') {
+            Some((start, end)) => {
+                let (start, end) = (parse(start)?, parse(end)?);
+                if start > end {
+                    return Err(format!(
+                        "{} is a reversed range (did you mean {}-{}?)",
+                        part.trim(),
+                        end,
+                        start
+                    ));
+                }
+                values.extend(start..=end);
+            }
+            None => values.push(parse(part)?),
+        }
+    }
+    Ok(List(values))
+}
+
+/// A list of probabilities, parsed as one argument like [List].
+#[derive(Clone)]
+struct Probabilities(Vec<f64>);
+
+/// Parse a comma-separated list of probabilities.
+fn parse_probabilities(s: &str) -> Result<Probabilities, String> {
+    s.split(',')
+        .map(|part| {
+            part.trim()
+                .parse()
+                .map_err(|_| format!("{} is not a probability", part))
+        })
+        .collect::<Result<_, _>>()
+        .map(Probabilities)
+}
+
+/// A row of the results, one per strategy and game configuration.
+struct Row {
+    /// The doors and opened doors, unknown for replayed traces
+    game: Option<(u32, u32)>,
+    strategy: String,
+    games: u64,
+    wins: u64,
+    losses: u64,
+    voided: u64,
+    win_rate: Option<f64>,
+    exact: Option<f64>,
+}
+
+const HEADER: [&str; 9] = [
+    "doors",
+    "opened_by_host",
+    "strategy",
+    "games",
+    "wins",
+    "losses",
+    "voided",
+    "win_rate",
+    "exact",
+];
+
+fn rows(game: Option<(u32, u32)>, results: &Results) -> Vec<Row> {
+    results
+        .result_sets()
+        .into_iter()
+        .map(|(strategy, set)| Row {
+            game,
+            exact: results
+                .exact_win_rate(&strategy)
+                .and_then(|rate| rate.to_f64()),
+            strategy,
+            games: set.games(),
+            wins: set.wins(),
+            losses: set.losses(),
+            voided: set.voided(),
+            win_rate: set.win_rate(),
+        })
+        .collect()
+}
+
+fn print_rows(rows: &[Row], format: Format) {
+    let rate = |rate: Option<f64>| rate.map_or_else(String::new, |rate| format!("{:.6}", rate));
+    let cells: Vec<[String; 9]> = rows
+        .iter()
+        .map(|row| {
+            [
+                row.game
+                    .map_or_else(String::new, |(doors, _)| doors.to_string()),
+                row.game
+                    .map_or_else(String::new, |(_, opened)| opened.to_string()),
+                row.strategy.clone(),
+                row.games.to_string(),
+                row.wins.to_string(),
+                row.losses.to_string(),
+                row.voided.to_string(),
+                rate(row.win_rate),
+                rate(row.exact),
+            ]
+        })
+        .collect();
+    match format {
+        Format::Csv => {
+            println!("{}", HEADER.join(","));
+            for row in cells {
+                // Replayed traces can name their strategies anything
+                let row: Vec<String> = row.iter().map(|cell| csv_field(cell)).collect();
+                println!("{}", row.join(","));
+            }
+        }
+        _ => {
+            let mut widths = HEADER.map(str::len);
+            for row in &cells {
+                for (width, cell) in widths.iter_mut().zip(row) {
+                    *width = (*width).max(cell.len());
+                }
+            }
+            let line = |row: &[String]| {
+                let cells: Vec<String> = row
+                    .iter()
+                    .zip(widths)
+                    .enumerate()
+                    // Left-align the strategy names, right-align the numbers
+                    .map(|(column, (cell, width))| match column {
+                        2 => format!("{:<width$}", cell, width = width),
+                        _ => format!("{:>width$}", cell, width = width),
+                    })
+                    .collect();
+                println!("{}", cells.join("  ").trim_end());
+            };
+            line(&HEADER.map(String::from));
+            for row in &cells {
+                line(row);
+            }
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<(), MontyError> {
+    match cli.command {
+        Command::Run {
+            doors,
+            opened,
+            strategies,
+            aggregate,
+            rules,
+            engine,
+            output,
+        } => {
+            let config = GameConfig::new(doors, opened)?.with_host(rules.host);
+            let players = if strategies.is_empty() {
+                Player::CLASSIC.to_vec()
+            } else {
+                strategies
+            };
+            let simulator = engine.simulator(rules.fidelity)?;
+            let results = if aggregate {
+                simulator.play_aggregate(&config, &players, engine.iterations)?
+            } else {
+                simulator.play(&config, &players, engine.iterations)?
+            };
+            match output.format {
+                Format::Json => println!("{}", results.to_json()?),
+                format => print_rows(&rows(Some((doors, opened)), &results), format),
+            }
+        }
+        Command::Sweep {
+            doors,
+            opened,
+            switch_probabilities,
+            rules,
+            engine,
+            output,
+        } => {
+            let sweep = Sweep::new()
+                .doors(doors.0)
+                .opened_by_host(opened.0)
+                .switch_probabilities(switch_probabilities.0)
+                .host(rules.host);
+            let simulator = engine.simulator(rules.fidelity)?;
+            let results = simulator.sweep(&sweep, engine.iterations)?;
+            match output.format {
+                Format::Json => println!("{}", results.to_json()?),
+                Format::Csv => print!("{}", results.to_csv()),
+                Format::Table => {
+                    let rows: Vec<Row> = results
+                        .iter()
+                        .flat_map(|(point, results)| {
+                            rows(Some((point.doors(), point.opened_by_host())), results)
+                        })
+                        .collect();
+                    print_rows(&rows, Format::Table);
+                }
+            }
+        }
+        Command::Trace {
+            output,
+            trace_format,
+            iterations,
+            doors,
+            opened,
+            strategies,
+            host,
+            fidelity,
+            seed,
+            rng,
+        } => {
+            let config = GameConfig::new(doors, opened)?.with_host(host);
+            let players = if strategies.is_empty() {
+                Player::CLASSIC.to_vec()
+            } else {
+                strategies
+            };
+            let mut monty = MontyHall::seeded_with(rng, seed).with_fidelity(fidelity);
+            // Checks the run before creating the file, so invalid runs leave nothing behind
+            let trace = monty.trace(&config, &players, iterations)?;
+            let file = File::create(&output).map_err(|err| MontyError::Trace(err.to_string()))?;
+            let mut writer = TraceWriter::new(BufWriter::new(file), trace_format, &players)?;
+            for record in trace {
+                writer.write(&record)?;
+            }
+            writer.finish()?;
+        }
+        Command::Replay { input, output } => {
+            let file = File::open(&input).map_err(|err| MontyError::Trace(err.to_string()))?;
+            let results = replay(BufReader::new(file))?;
+            match output.format {
+                Format::Json => println!("{}", results.to_json()?),
+                format => print_rows(&rows(None, &results), format),
+            }
+        }
+        Command::Bench {
+            iterations,
+            threads,
+            rng,
+            backend,
+            output,
+        } => {
+            let engine = Engine {
+                iterations,
+                threads,
+                seed: 0,
+                rng,
+                backend,
+            };
+            let simulator = engine.simulator(Fidelity::Fast)?;
+            let started = Instant::now();
+            simulator.play(&GameConfig::default(), &Player::CLASSIC, iterations)?;
+            let seconds = started.elapsed().as_secs_f64();
+            let rate = iterations as f64 / seconds;
+            match output.format {
+                Format::Table => println!(
+                    "Played {} games on {} threads with the {} backend in {:.3}s ({:.1} million games/s)",
+                    iterations,
+                    simulator.threads(),
+                    simulator.backend(),
+                    seconds,
+                    rate / 1e6
+                ),
+                Format::Json => println!(
+                    r#"{{"iterations":{},"threads":{},"backend":"{}","seconds":{},"games_per_second":{}}}"#,
+                    iterations,
+                    simulator.threads(),
+                    simulator.backend(),
+                    seconds,
+                    rate
+                ),
+                Format::Csv => {
+                    println!("iterations,threads,backend,seconds,games_per_second");
+                    println!(
+                        "{},{},{},{},{}",
+                        iterations,
+                        simulator.threads(),
+                        simulator.backend(),
+                        seconds,
+                        rate
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(err) = run(Cli::parse()) {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
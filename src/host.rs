@@ -0,0 +1,315 @@
+//! Models of how the host behaves once the player has made their initial choice.
+//!
+//! The classic problem assumes a host who knows where the car is and always
+//! opens goat doors. Changing that assumption changes the answer, which is what
+//! the variants in [Host] are for. See the
+//! [variants section](https://en.wikipedia.org/wiki/Monty_Hall_problem#Variants)
+//! of the Wikipedia article for the expected results.
+
+use crate::{uniform_below, Fidelity, MontyError};
+use derive_more::Display;
+use rand_core::RngCore;
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr, sync::Arc};
+
+/// What happens after the host has opened the doors.
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HostAction {
+    /// The player is offered the chance to switch.
+    #[display(fmt = "offer")]
+    Offer,
+    /// The player has to keep the initial choice.
+    #[display(fmt = "no_offer")]
+    NoOffer,
+    /// The car was revealed, so the game does not count.
+    #[display(fmt = "void")]
+    Void,
+}
+
+/// The state of a game at the moment the host steps in.
+pub struct Round<'a> {
+    car: u32,
+    choice: u32,
+    to_open: u32,
+    fidelity: Fidelity,
+    closed: &'a mut Vec<u32>,
+    opened: &'a mut Vec<u32>,
+}
+
+impl<'a> Round<'a> {
+    pub(crate) fn new(
+        car: u32,
+        choice: u32,
+        to_open: u32,
+        fidelity: Fidelity,
+        closed: &'a mut Vec<u32>,
+        opened: &'a mut Vec<u32>,
+    ) -> Self {
+        Self {
+            car,
+            choice,
+            to_open,
+            fidelity,
+            closed,
+            opened,
+        }
+    }
+
+    /// The door hiding the car.
+    pub fn car(&self) -> u32 {
+        self.car
+    }
+
+    /// The door chosen by the player.
+    pub fn choice(&self) -> u32 {
+        self.choice
+    }
+
+    /// The number of doors the host still has to open.
+    pub fn to_open(&self) -> u32 {
+        self.to_open
+    }
+
+    /// Whether the host should take shortcuts when choosing doors.
+    pub fn fidelity(&self) -> Fidelity {
+        self.fidelity
+    }
+
+    /// The doors other than the player's choice that are still closed, in ascending order.
+    pub fn closed(&self) -> &[u32] {
+        self.closed
+    }
+
+    /// The doors opened so far, in the order they were opened.
+    pub fn opened(&self) -> &[u32] {
+        self.opened
+    }
+
+    /// Open the door at `index` in [Round::closed], returning the opened door.
+    pub fn open(&mut self, index: usize) -> u32 {
+        self.to_open = self.to_open.saturating_sub(1);
+        let door = self.closed.remove(index);
+        self.opened.push(door);
+        door
+    }
+
+    /// Open a random closed door, which may be the car.
+    pub fn open_random(&mut self, rng: &mut dyn RngCore) -> u32 {
+        let index = uniform_below(rng, self.closed.len() as u32);
+        self.open(index as usize)
+    }
+
+    /// Open goat doors until no more doors have to be opened, taking the
+    /// shortcut of [Round::open_first_goats] unless the fidelity is [Fidelity::Faithful].
+    pub fn open_goats(&mut self, rng: &mut dyn RngCore) {
+        match self.fidelity {
+            Fidelity::Fast => self.open_first_goats(),
+            Fidelity::Faithful => self.open_random_goats(rng),
+        }
+    }
+
+    /// Open randomly chosen goat doors until no more doors have to be opened.
+    pub fn open_random_goats(&mut self, rng: &mut dyn RngCore) {
+        let car = self.car;
+        let opened = &mut *self.opened;
+        let mut to_open = self.to_open;
+        let mut goats = self.closed.len() as u32 - self.closed.contains(&car) as u32;
+        // Selection sampling: each goat is opened with probability
+        // (doors left to open) / (goats left to consider)
+        self.closed.retain(|&door| {
+            if to_open == 0 || door == car {
+                return true;
+            }
+            let open = to_open == goats || uniform_below(rng, goats) < to_open;
+            goats -= 1;
+            if open {
+                to_open -= 1;
+                opened.push(door);
+            }
+            !open
+        });
+        self.to_open = 0;
+    }
+
+    /// Open the lowest-numbered goat doors until no more doors have to be opened.
+    pub fn open_first_goats(&mut self) {
+        let car = self.car;
+        let opened = &mut *self.opened;
+        let mut to_open = self.to_open;
+        self.closed.retain(|&door| {
+            if to_open > 0 && door != car {
+                to_open -= 1;
+                opened.push(door);
+                false
+            } else {
+                true
+            }
+        });
+        self.to_open = 0;
+    }
+}
+
+/// Decides which doors the host opens and whether the player gets to switch.
+///
+/// Implement this to simulate a host not covered by [Host] and pass it in
+/// using [Host::Custom]. Games where the host opens fewer doors than asked,
+/// or leaves no door to switch to, are voided.
+///
+/// ```rust
+/// use monty_pyrs::{GameConfig, Host, HostAction, HostStrategy, MontyHall, Outcome, Player, Round};
+/// use rand_core::RngCore;
+/// use std::sync::Arc;
+///
+/// /// Opens every door it can, however many it was asked to open
+/// struct Overeager;
+///
+/// impl HostStrategy for Overeager {
+///     fn open_doors(&self, _rng: &mut dyn RngCore, round: &mut Round) -> HostAction {
+///         while !round.closed().is_empty() {
+///             round.open(0);
+///         }
+///         HostAction::Offer
+///     }
+///
+///     fn name(&self) -> &str {
+///         "overeager"
+///     }
+/// }
+///
+/// let config = GameConfig::default().with_host(Host::Custom(Arc::new(Overeager)));
+/// let outcome = MontyHall::default().play_single(&config, &Player::AlwaysSwitch);
+/// assert_eq!(outcome, Outcome::Voided);
+/// ```
+pub trait HostStrategy: Send + Sync {
+    /// Open [Round::to_open] doors and decide whether to offer a switch.
+    fn open_doors(&self, rng: &mut dyn RngCore, round: &mut Round) -> HostAction;
+
+    /// A short name identifying the host.
+    fn name(&self) -> &str;
+}
+
+/// The built-in host models.
+///
+/// ```rust
+/// use monty_pyrs::{GameConfig, Host, MontyHall, Outcome, Player};
+///
+/// let mut monty = MontyHall::default();
+/// let player = Player::AlwaysSwitch;
+///
+/// // The angelic host only offers a switch when you picked wrong...
+/// let config = GameConfig::default().with_host(Host::Angelic);
+/// assert!((0..1000).all(|_| monty.play_single(&config, &player) == Outcome::Won));
+///
+/// // ...while the devilish host only offers a switch when you picked right
+/// let config = GameConfig::default().with_host(Host::Devilish);
+/// assert!((0..1000).all(|_| monty.play_single(&config, &player) == Outcome::Lost));
+/// ```
+#[derive(Clone, Default)]
+pub enum Host {
+    /// Knows where the car is and always opens goat doors. The classic problem.
+    /// Picks the goat doors at random when running [Fidelity::Faithful] games.
+    #[default]
+    Knowledgeable,
+    /// Opens random doors. Games where the car is revealed are voided, so the
+    /// results are conditioned on a goat being revealed.
+    Ignorant,
+    /// Slips and accidentally forces open one random door, then knowingly opens
+    /// any further goat doors. Games where the fall reveals the car are voided.
+    /// With a single opened door this is the same as [Host::Ignorant].
+    MontyFall,
+    /// Knows where the car is and always opens the lowest-numbered goat doors,
+    /// regardless of [Fidelity].
+    MontyCrawl,
+    /// Opens goat doors, but only offers a switch when the player picked wrong.
+    Angelic,
+    /// Opens goat doors, but only offers a switch when the player picked right.
+    Devilish,
+    /// A user-provided host.
+    Custom(Arc<dyn HostStrategy>),
+}
+
+impl HostStrategy for Host {
+    fn open_doors(&self, rng: &mut dyn RngCore, round: &mut Round) -> HostAction {
+        match self {
+            Host::Knowledgeable => {
+                round.open_goats(rng);
+                HostAction::Offer
+            }
+            Host::MontyCrawl => {
+                round.open_first_goats();
+                HostAction::Offer
+            }
+            Host::Ignorant => {
+                let mut revealed_car = false;
+                while round.to_open() > 0 {
+                    revealed_car |= round.open_random(rng) == round.car();
+                }
+                if revealed_car {
+                    HostAction::Void
+                } else {
+                    HostAction::Offer
+                }
+            }
+            Host::MontyFall => {
+                if round.to_open() > 0 && round.open_random(rng) == round.car() {
+                    return HostAction::Void;
+                }
+                round.open_goats(rng);
+                HostAction::Offer
+            }
+            Host::Angelic | Host::Devilish => {
+                round.open_goats(rng);
+                let picked_right = round.choice() == round.car();
+                if picked_right == matches!(self, Host::Devilish) {
+                    HostAction::Offer
+                } else {
+                    HostAction::NoOffer
+                }
+            }
+            Host::Custom(host) => host.open_doors(rng, round),
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Host::Knowledgeable => "knowledgeable",
+            Host::Ignorant => "ignorant",
+            Host::MontyFall => "monty_fall",
+            Host::MontyCrawl => "monty_crawl",
+            Host::Angelic => "angelic",
+            Host::Devilish => "devilish",
+            Host::Custom(host) => host.name(),
+        }
+    }
+}
+
+/// Hosts are compared by name, so custom hosts with the same name are considered equal.
+impl PartialEq for Host {
+    fn eq(&self, other: &Self) -> bool {
+        self.name() == other.name()
+    }
+}
+
+impl fmt::Debug for Host {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parses the names of the built-in hosts, as returned by [HostStrategy::name].
+impl FromStr for Host {
+    type Err = MontyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "knowledgeable" => Ok(Host::Knowledgeable),
+            "ignorant" => Ok(Host::Ignorant),
+            "monty_fall" => Ok(Host::MontyFall),
+            "monty_crawl" => Ok(Host::MontyCrawl),
+            "angelic" => Ok(Host::Angelic),
+            "devilish" => Ok(Host::Devilish),
+            _ => Err(MontyError::UnknownHost(s.to_string())),
+        }
+    }
+}
@@ -0,0 +1,192 @@
+//! A bit-parallel kernel playing 64 games per random word.
+//!
+//! With a host that never reveals the car, whether always staying wins only
+//! depends on whether the car is behind the picked door, which happens with
+//! probability `1 / doors`. Whether always switching wins additionally depends
+//! on whether the door switched to hides the car, which happens with
+//! probability `1 / closed` for the `closed` doors the host leaves to switch to.
+//! Rather than playing out every game, the kernel draws these events for 64
+//! games at once, one game per bit, and counts the wins with popcount.
+//!
+//! [MontyHall::play_multiple](crate::MontyHall::play_multiple) uses the kernel
+//! for [Fidelity::Fast] games when all strategies are covered by it, running
+//! it on the chosen [Backend](crate::Backend). The games
+//! are not the ones [MontyHall::play_single](crate::MontyHall::play_single)
+//! would have played with the same generator, but they are won as often:
+//!
+//! ```rust
+//! use monty_pyrs::{exact_win_rate, GameConfig, Host, MontyHall, Outcome, Player, PlayerStrategy};
+//! use assert_approx_eq::assert_approx_eq;
+//! use num_traits::ToPrimitive;
+//!
+//! let games = [
+//!     (3, 1, Host::Knowledgeable),
+//!     (8, 6, Host::Knowledgeable),
+//!     (10, 3, Host::MontyCrawl),
+//!     (4, 1, Host::Angelic),
+//!     (5, 2, Host::Devilish),
+//! ];
+//! for (doors, opened, host) in games {
+//!     let config = GameConfig::new(doors, opened).unwrap().with_host(host);
+//!     let results = MontyHall::default().play_multiple(&config, &Player::CLASSIC, 1_000_000).unwrap();
+//!     let mut monty = MontyHall::default();
+//!     for player in Player::CLASSIC {
+//!         let won = (0..200_000)
+//!             .filter(|_| monty.play_single(&config, &player) == Outcome::Won)
+//!             .count();
+//!         let batched = results.win_rate(&player.name()).unwrap();
+//!         assert_approx_eq!(batched, won as f64 / 200_000., 0.01);
+//!         let exact = exact_win_rate(&config, &player).unwrap().to_f64().unwrap();
+//!         assert_approx_eq!(batched, exact, 0.005);
+//!     }
+//! }
+//! ```
+//!
+//! Under a fixed seed the kernel plays the same games every time:
+//!
+//! ```rust
+//! use monty_pyrs::{GameConfig, MontyHall, Player};
+//!
+//! let config = GameConfig::new(7, 4).unwrap();
+//! let first = MontyHall::seeded(5).play_multiple(&config, &Player::CLASSIC, 100_003).unwrap();
+//! let second = MontyHall::seeded(5).play_multiple(&config, &Player::CLASSIC, 100_003).unwrap();
+//! let other = MontyHall::seeded(6).play_multiple(&config, &Player::CLASSIC, 100_003).unwrap();
+//! assert_eq!(first, second);
+//! assert_ne!(first.result_sets(), other.result_sets());
+//! assert_eq!(first.games("always_switch"), Some(50_002));
+//! ```
+
+use crate::simd::Lanes;
+use crate::{Fidelity, GameConfig, Host, Player, ResultSet};
+use rand_core::RngCore;
+
+/// Draw bits that are each set with probability `1 / n`, taking random words from `next`.
+///
+/// Every bit is the comparison of a uniform number with `1 / n`, both written
+/// out in binary one digit at a time: the bits of a random word are the next
+/// digits of the uniform numbers, and a comparison is decided at the first
+/// digit that differs from `1 / n`. About half of the undecided bits are
+/// decided by each word, so the draw is exact and takes a handful of words.
+///
+/// # Safety
+///
+/// The CPU features `V` needs must be enabled, see [Lanes].
+#[inline(always)]
+unsafe fn one_in<V: Lanes>(next: &mut impl FnMut() -> V, n: u32) -> V {
+    debug_assert!(n > 0, "can't draw with probability 1 / 0");
+    if n == 1 {
+        return V::splat(!0);
+    }
+    let n = u64::from(n);
+    // The digits of 1 / n left to write out are `remainder / n`
+    let mut remainder = 1;
+    let mut undecided = V::splat(!0);
+    let mut below = V::splat(0);
+    while remainder != 0 && undecided.any() {
+        remainder <<= 1;
+        let word = next();
+        if remainder >= n {
+            remainder -= n;
+            // The digit of 1 / n is 1, so a 0 decides that the number is below
+            below = below.or(undecided.and_not(word));
+            undecided = undecided.and(word);
+        } else {
+            undecided = undecided.and_not(word);
+        }
+    }
+    // Numbers still undecided once the digits run out are equal to 1 / n so
+    // far and can only be above it
+    below
+}
+
+/// What the host does when the player picked wrong and the player always switches.
+#[derive(Debug, Clone, Copy)]
+enum Switching {
+    /// The host offers a switch either way.
+    Offered,
+    /// The host only offers a switch when the player picked wrong.
+    Angelic,
+    /// The host only offers a switch when the player picked right.
+    Devilish,
+}
+
+/// Plays games of a strategy in bulk, for the games and strategies that
+/// [Kernel::new] allows.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Kernel {
+    doors: u32,
+    /// The doors left closed to switch to, including the car when picked wrong
+    closed: u32,
+    /// `None` when always staying
+    switching: Option<Switching>,
+}
+
+impl Kernel {
+    /// The kernel for `player` in the game, if whether it wins only depends on
+    /// where the car is and the shortcuts of [Fidelity::Fast] are taken.
+    pub(crate) fn new(config: &GameConfig, fidelity: Fidelity, player: &Player) -> Option<Self> {
+        if fidelity != Fidelity::Fast {
+            return None;
+        }
+        let switching = match config.host() {
+            Host::Knowledgeable | Host::MontyCrawl => Switching::Offered,
+            Host::Angelic => Switching::Angelic,
+            Host::Devilish => Switching::Devilish,
+            _ => return None,
+        };
+        let switching = match player {
+            Player::AlwaysSwitch => Some(switching),
+            Player::AlwaysStay => None,
+            _ => return None,
+        };
+        Some(Self {
+            doors: config.doors(),
+            closed: config.doors() - 1 - config.opened_by_host(),
+            switching,
+        })
+    }
+
+    /// Play `games` games, one word of 64 games at a time.
+    pub(crate) fn play<R: RngCore + ?Sized>(&self, rng: &mut R, games: u64) -> ResultSet {
+        // Single words need no CPU features
+        unsafe { self.play_lanes::<u64>(|| rng.next_u64(), games) }
+    }
+
+    /// Play `games` games, 64 per bit of the registers of type `V`, taking
+    /// random registers from `next`.
+    ///
+    /// # Safety
+    ///
+    /// The CPU features `V` needs must be enabled, see [Lanes].
+    #[inline(always)]
+    pub(crate) unsafe fn play_lanes<V: Lanes>(
+        &self,
+        mut next: impl FnMut() -> V,
+        games: u64,
+    ) -> ResultSet {
+        let step = 64 * V::WORDS as u64;
+        let mut wins = 0;
+        let mut played = 0;
+        while played < games {
+            let picked_car = one_in(&mut next, self.doors);
+            let won = match self.switching {
+                None => picked_car,
+                Some(Switching::Offered) => one_in(&mut next, self.closed).and_not(picked_car),
+                Some(Switching::Angelic) => picked_car.or(one_in(&mut next, self.closed)),
+                Some(Switching::Devilish) => V::splat(0),
+            };
+            let won = if games - played < step {
+                won.and(V::first_games(games - played))
+            } else {
+                won
+            };
+            wins += won.count_ones();
+            played += step.min(games - played);
+        }
+        ResultSet {
+            wins,
+            losses: games - wins,
+            voided: 0,
+        }
+    }
+}
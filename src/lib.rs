@@ -7,66 +7,1119 @@
 //! This project aims to be the fastest Monty Hall simulator in existence.
 //! To that end, some corners are cut:
 //!
-//! - Random number generation is fast rather than properly random
+//! - Random number generation is fast rather than properly random, unless
+//!   another [RngKind] is chosen
 //! - The first option is always chosen as the initial guess
-//! - When an incorrect option is removed following the initial choice, the
-//! simulation does not randomly pick an option to remove, it simply removes
-//! the first option. Half the time (ie. when the initial choice is not
-//! correct and there is only one possible option that can be removed) this
-//! makes no difference, and regardless, it doesn't really matter. What we
-//! care about is whether switching is more successful than not switching.
+//! - When incorrect options are removed following the initial choice, the
+//!   simulation does not randomly pick the options to remove, it simply removes
+//!   the first ones. In the classic game, half the time (ie. when the initial
+//!   choice is not correct and there is only one possible option that can be
+//!   removed) this makes no difference, and regardless, it doesn't really matter.
+//!   What we care about is whether switching is more successful than not switching.
+//! - When only always switching and always staying are played, against a host
+//!   that never reveals the car, games aren't played out at all: 64 games at a
+//!   time are decided by whether the car is behind the picked door, and for
+//!   switching whether it is behind the door switched to
+//!
+//! The second and third shortcuts do matter for strategies that look at which doors
+//! the host opened. [Fidelity::Faithful] turns them off, along with the last one.
+//!
+//! The classic game has three doors of which the host opens one, but any
+//! number of doors can be simulated by passing a [GameConfig]. The config also
+//! selects the [Host] model, which covers the well-known variants of the problem.
 //!
 //! This is likely also the silliest Monty Hall problem simulator in existence.
 //! This is a non-goal.
 
-use derive_more::AddAssign; // Adds += overload for Results struct
+use derive_more::{AddAssign, Display}; // Adds += overload for ResultSet struct and Display for errors
+use kernel::Kernel; // Plays the classic strategies 64 games at a time
+use num_rational::BigRational; // Exact win probabilities
+use num_traits::ToPrimitive; // Converts exact win probabilities to floats
+use pyo3::basic::CompareOp; // Distinguishes == from the other comparisons in __richcmp__
+use pyo3::exceptions::PyKeyError; // Python exception raised for unknown strategies
+use pyo3::exceptions::PyValueError; // Python exception raised for invalid input
 use pyo3::prelude::*; // Macros for exposing Rust code to Python
+use pyo3::types::{PyBytes, PyDict}; // Results converted to dicts and bytes for Python
 use rand_core::{RngCore, SeedableRng}; // Traits for generating random numbers and seeding
 use rand_xorshift::XorShiftRng; // The fastest possible (?) random number generator
-use tinyvec::{array_vec, ArrayVec}; // The smallest possible (?) data structure that implements removal
+use serde::{Deserialize, Serialize}; // Saving results to combine runs across machines
+use simd::LaneRng; // Random numbers for the vectorized kernel
+use std::collections::HashMap; // Expected win rates passed in from Python
+use std::fmt; // Human-readable summaries of the results
+use std::ops::Range; // Identifies the games played by each chunk of work
+use std::str::FromStr; // Fidelities named in Python
+use std::time::Duration; // Sets the interval between progress reports
+
+mod aggregate;
+mod analytical;
+mod host;
+mod kernel;
+mod metadata;
+mod player;
+mod python;
+mod rng;
+mod simd;
+mod simulator;
+mod stats;
+mod sweep;
+mod trace;
+mod trace_io;
+
+pub use analytical::{exact_void_rate, exact_win_rate};
+pub use host::{Host, HostAction, HostStrategy, Round};
+pub use metadata::RunMetadata;
+pub use player::{Player, PlayerStrategy, Reveal};
+pub use rng::{uniform_below, AnyRng, RngKind, SplitMix64, WyRand};
+pub use simd::Backend;
+pub use simulator::{CancellationToken, Progress, Simulator, SimulatorBuilder};
+pub use sweep::{Sweep, SweepPoint, SweepResults};
+pub use trace::{GameRecord, Trace};
+pub use trace_io::{csv_field, replay, TraceFormat, TraceWriter};
+
+/// How many games [MontyHall] plays between checks of its [CancellationToken].
+pub const CANCELLATION_CHECK_INTERVAL: u64 = 1024;
+
+/// Errors that can occur when setting up a simulation.
+#[derive(Debug, Display, Clone, PartialEq, Eq)]
+pub enum MontyError {
+    /// The host has to leave the chosen door and at least one other door closed.
+    #[display(
+        fmt = "invalid game: the host cannot open {} of {} doors (at most doors - 2 can be opened)",
+        opened_by_host,
+        doors
+    )]
+    InvalidConfig { doors: u32, opened_by_host: u32 },
+    /// The name does not match any of the built-in hosts.
+    #[display(fmt = "unknown host model: {}", _0)]
+    UnknownHost(String),
+    /// The name does not match any of the built-in player strategies.
+    #[display(fmt = "unknown player strategy: {}", _0)]
+    UnknownPlayer(String),
+    /// Simulations need at least one thread to run on.
+    #[display(fmt = "the number of threads must be at least 1")]
+    InvalidThreads,
+    /// The worker threads could not be started.
+    #[display(fmt = "failed to start worker threads: {}", _0)]
+    ThreadPool(String),
+    /// At least one strategy has to be played.
+    #[display(fmt = "at least one strategy must be played")]
+    NoStrategies,
+    /// Every strategy has to play at least one game.
+    #[display(
+        fmt = "{} iterations are too few for {} strategies: each strategy must play at least one game",
+        iterations,
+        strategies
+    )]
+    TooFewIterations { iterations: u64, strategies: usize },
+    /// The strategy was not played.
+    #[display(fmt = "no games were played with strategy {}", _0)]
+    NoGamesPlayed(String),
+    /// The strategy was not played, or all of its games were voided.
+    #[display(fmt = "no games were decided with strategy {}", _0)]
+    NoDecidedGames(String),
+    /// Only results of the same game can be merged.
+    #[display(fmt = "cannot merge results of different games: {} and {}", _0, _1)]
+    IncompatibleRuns(String, String),
+    /// Merging runs with the same seed would count the same games twice.
+    #[display(fmt = "both results contain games played with seed {}", _0)]
+    DuplicateSeed(u64),
+    /// Results could not be saved or loaded.
+    #[display(fmt = "failed to save or load results: {}", _0)]
+    Serialization(String),
+    /// Confidence levels and expected win rates are probabilities.
+    #[display(fmt = "{} must be strictly between 0 and 1", _0)]
+    InvalidProbability(String),
+    /// Players can't switch more often than always or less often than never.
+    #[display(fmt = "switch probability {} is not between 0 and 1", _0)]
+    InvalidSwitchProbability(String),
+    /// A trace of games could not be written or read.
+    #[display(fmt = "failed to write or read trace: {}", _0)]
+    Trace(String),
+    /// The name does not match any [Fidelity].
+    #[display(fmt = "unknown fidelity: {} (expected fast or faithful)", _0)]
+    UnknownFidelity(String),
+    /// The name does not match any [RngKind].
+    #[display(
+        fmt = "unknown random number generator: {} (expected xorshift, pcg64, chacha8, chacha20, wyrand or splitmix)",
+        _0
+    )]
+    UnknownRng(String),
+    /// The name does not match any [Backend].
+    #[display(
+        fmt = "unknown backend: {} (expected auto, scalar, portable, sse2, avx2 or avx512)",
+        _0
+    )]
+    UnknownBackend(String),
+    /// The name does not match any [TraceFormat].
+    #[display(fmt = "unknown trace format: {} (expected csv, ndjson or binary)", _0)]
+    UnknownTraceFormat(String),
+    /// The CPU lacks the features the [Backend] needs.
+    #[display(fmt = "the {} backend is not supported by this CPU", _0)]
+    UnsupportedBackend(String),
+    /// The vectorized backends play with their own generators, not the chosen one.
+    #[display(
+        fmt = "the {} backend can't play with the {} generator (only with xorshift)",
+        _0,
+        _1
+    )]
+    IncompatibleBackend(String, String),
+    /// Only strategies with an [exact_win_rate] can be sampled in aggregate.
+    #[display(
+        fmt = "the results of strategy {} can't be sampled: its exact win rate is unknown",
+        _0
+    )]
+    NoExactDistribution(String),
+    /// None of the combinations of the swept parameters is a valid game.
+    #[display(
+        fmt = "none of the swept door counts allows any of the swept numbers of opened doors"
+    )]
+    EmptySweep,
+}
+
+impl std::error::Error for MontyError {}
+
+impl From<MontyError> for PyErr {
+    fn from(err: MontyError) -> PyErr {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+/// Describes the game being played: how many doors there are, how many
+/// doors the host opens before offering the switch and how the host behaves.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameConfig {
+    doors: u32,
+    opened_by_host: u32,
+    host: Host,
+}
+
+impl GameConfig {
+    /// Create a game with `doors` doors of which the host opens `opened_by_host`.
+    ///
+    /// The host must leave the chosen door and at least one other door closed,
+    /// so `opened_by_host` can be at most `doors - 2`.
+    ///
+    /// ```rust
+    /// use monty_pyrs::GameConfig;
+    ///
+    /// let config = GameConfig::new(100, 98).unwrap();
+    /// assert_eq!(config.doors(), 100);
+    /// assert!(GameConfig::new(10, 9).is_err());
+    /// assert!(GameConfig::new(1, 0).is_err());
+    /// ```
+    pub fn new(doors: u32, opened_by_host: u32) -> Result<Self, MontyError> {
+        match doors.checked_sub(2) {
+            Some(max_opened) if opened_by_host <= max_opened => Ok(Self {
+                doors,
+                opened_by_host,
+                host: Host::default(),
+            }),
+            _ => Err(MontyError::InvalidConfig {
+                doors,
+                opened_by_host,
+            }),
+        }
+    }
+
+    /// The total number of doors.
+    pub fn doors(&self) -> u32 {
+        self.doors
+    }
+
+    /// The number of doors the host opens after the initial choice.
+    pub fn opened_by_host(&self) -> u32 {
+        self.opened_by_host
+    }
+
+    /// Use a different host model. The default is [Host::Knowledgeable].
+    pub fn with_host(mut self, host: Host) -> Self {
+        self.host = host;
+        self
+    }
+
+    /// The host model.
+    pub fn host(&self) -> &Host {
+        &self.host
+    }
+}
+
+/// The classic game: three doors, one of which is opened by the host.
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            doors: 3,
+            opened_by_host: 1,
+            host: Host::default(),
+        }
+    }
+}
+
+/// How closely the simulation follows the rules of the game.
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Fidelity {
+    /// Always pick the first door initially and let the host open the
+    /// first eligible goat doors. Fast, and good enough for strategies that
+    /// don't care which doors were opened.
+    #[default]
+    #[display(fmt = "fast")]
+    Fast,
+    /// Pick the initial door at random and let the host choose randomly among
+    /// the eligible goat doors.
+    #[display(fmt = "faithful")]
+    Faithful,
+}
 
-/// Inner struct for [Results](struct.Results.html) that tracks wins and losses for
-/// a given strategy
-#[derive(Default, AddAssign)]
-struct ResultSet {
+/// Parses the names printed by [Display](fmt::Display).
+///
+/// ```rust
+/// use monty_pyrs::Fidelity;
+///
+/// assert_eq!("faithful".parse(), Ok(Fidelity::Faithful));
+/// assert!("exact".parse::<Fidelity>().is_err());
+/// ```
+impl FromStr for Fidelity {
+    type Err = MontyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fast" => Ok(Fidelity::Fast),
+            "faithful" => Ok(Fidelity::Faithful),
+            _ => Err(MontyError::UnknownFidelity(s.to_string())),
+        }
+    }
+}
+
+/// The outcome of a single game.
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    #[display(fmt = "won")]
+    Won,
+    #[display(fmt = "lost")]
+    Lost,
+    /// The host revealed the car, so the game does not count.
+    #[display(fmt = "voided")]
+    Voided,
+}
+
+/// The wins, losses and voided games of a single strategy, as tracked by
+/// [Results](struct.Results.html).
+#[derive(Debug, Default, Clone, PartialEq, Eq, AddAssign, Serialize, Deserialize)]
+#[pyclass(module = "monty_pyrs")]
+pub struct ResultSet {
     wins: u64,
     losses: u64,
+    voided: u64,
+}
+
+impl ResultSet {
+    fn record(&mut self, outcome: Outcome) {
+        match outcome {
+            Outcome::Won => self.wins += 1,
+            Outcome::Lost => self.losses += 1,
+            Outcome::Voided => self.voided += 1,
+        }
+    }
+}
+
+#[pymethods]
+impl ResultSet {
+    #[new]
+    #[args(wins = "0", losses = "0", voided = "0")]
+    pub fn new(wins: u64, losses: u64, voided: u64) -> Self {
+        Self {
+            wins,
+            losses,
+            voided,
+        }
+    }
+
+    /// The number of games won.
+    #[getter]
+    pub fn wins(&self) -> u64 {
+        self.wins
+    }
+
+    /// The number of games lost.
+    #[getter]
+    pub fn losses(&self) -> u64 {
+        self.losses
+    }
+
+    /// The number of games voided because the host revealed the car.
+    #[getter]
+    pub fn voided(&self) -> u64 {
+        self.voided
+    }
+
+    /// The number of games played, including voided games.
+    #[getter]
+    pub fn games(&self) -> u64 {
+        self.wins + self.losses + self.voided
+    }
+
+    /// The games that were not voided, which the win rate is calculated over.
+    #[getter]
+    pub fn decided(&self) -> u64 {
+        self.wins + self.losses
+    }
+
+    /// The share of decided games that were won, if any were decided.
+    #[getter]
+    pub fn win_rate(&self) -> Option<f64> {
+        match self.decided() {
+            0 => None,
+            decided => Some(self.wins as f64 / decided as f64),
+        }
+    }
+
+    /// The share of games that were voided, if any were played.
+    #[getter]
+    pub fn void_rate(&self) -> Option<f64> {
+        match self.games() {
+            0 => None,
+            games => Some(self.voided as f64 / games as f64),
+        }
+    }
+
+    /// The counters and rates as a dict, with `None` for undefined rates.
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let dict = PyDict::new(py);
+        dict.set_item("wins", self.wins)?;
+        dict.set_item("losses", self.losses)?;
+        dict.set_item("voided", self.voided)?;
+        dict.set_item("games", self.games())?;
+        dict.set_item("win_rate", self.win_rate())?;
+        dict.set_item("void_rate", self.void_rate())?;
+        Ok(dict)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ResultSet(wins={}, losses={}, voided={})",
+            self.wins, self.losses, self.voided
+        )
+    }
+
+    fn __str__(&self) -> String {
+        self.to_string()
+    }
+
+    fn __richcmp__(&self, other: PyRef<Self>, op: CompareOp, py: Python) -> PyObject {
+        match op {
+            CompareOp::Eq => (*self == *other).into_py(py),
+            CompareOp::Ne => (*self != *other).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    fn __add__(&self, other: PyRef<Self>) -> Self {
+        let mut sum = self.clone();
+        sum += other.clone();
+        sum
+    }
+
+    fn __iadd__(&mut self, other: Self) {
+        *self += other;
+    }
+
+    fn __getstate__(&self) -> (u64, u64, u64) {
+        (self.wins, self.losses, self.voided)
+    }
+
+    fn __setstate__(&mut self, state: (u64, u64, u64)) {
+        let (wins, losses, voided) = state;
+        *self = Self::new(wins, losses, voided);
+    }
+}
+
+/// Summarises the games, eg. `66.67% won (2 won, 1 lost, 0 voided)`.
+impl fmt::Display for ResultSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.win_rate() {
+            Some(rate) => write!(f, "{:.2}% won", rate * 100.)?,
+            None => f.write_str("no games decided")?,
+        }
+        write!(
+            f,
+            " ({} won, {} lost, {} voided)",
+            self.wins, self.losses, self.voided
+        )
+    }
+}
+
+/// The win rates of the classic game, which [Results::chi_squared_test] tests
+/// against when called from Python without expected win rates.
+const CLASSIC_WIN_RATES: [(&str, f64); 2] = [("always_switch", 2. / 3.), ("always_stay", 1. / 3.)];
+
+/// Check that every strategy can be played and gets to play at least one
+/// game, so that all win rates are defined.
+pub(crate) fn check_iterations(players: &[Player], iterations: u64) -> Result<(), MontyError> {
+    if players.is_empty() {
+        return Err(MontyError::NoStrategies);
+    }
+    for player in players {
+        player.validate()?;
+    }
+    if iterations < players.len() as u64 {
+        return Err(MontyError::TooFewIterations {
+            iterations,
+            strategies: players.len(),
+        });
+    }
+    Ok(())
 }
 
-/// Tracks results for the two possible strategies: switching and staying.
-#[derive(Default, AddAssign)]
-#[pyclass]
+/// Confidence levels and expected win rates exclude certainty either way.
+fn check_probability(value: f64, description: impl FnOnce() -> String) -> Result<(), MontyError> {
+    if value > 0. && value < 1. {
+        Ok(())
+    } else {
+        Err(MontyError::InvalidProbability(description()))
+    }
+}
+
+/// Tracks results for each strategy played, keyed by the strategy name,
+/// along with the [RunMetadata] of the runs that played them.
+///
+/// Results can be saved as JSON or in a compact binary form, and results of
+/// the same game played elsewhere merged in with [Results::merge].
+///
+/// ```rust
+/// use monty_pyrs::{GameConfig, Player, Results, play_threaded_seeded};
+///
+/// let config = GameConfig::default();
+/// let mut results = play_threaded_seeded(&config, &Player::CLASSIC, 1_000_000, 1).unwrap();
+/// // Eg. played on another machine
+/// let json = play_threaded_seeded(&config, &Player::CLASSIC, 1_000_000, 2).unwrap().to_json().unwrap();
+///
+/// results.merge(Results::from_json(&json).unwrap()).unwrap();
+/// assert_eq!(results.total_games(), 2_000_000);
+/// assert_eq!(results.metadata().len(), 2);
+/// assert_eq!(Results::from_bytes(&results.to_bytes().unwrap()).unwrap(), results);
+///
+/// // The first run can't be merged in again, nor can runs of different games
+/// assert!(results.merge(play_threaded_seeded(&config, &Player::CLASSIC, 10, 1).unwrap()).is_err());
+/// let config = GameConfig::new(4, 2).unwrap();
+/// assert!(results.merge(play_threaded_seeded(&config, &Player::CLASSIC, 10, 3).unwrap()).is_err());
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[pyclass(module = "monty_pyrs")]
 pub struct Results {
-    switched: ResultSet,
-    stayed: ResultSet,
+    strategies: Vec<(String, ResultSet)>,
+    /// Set when the run was cancelled before all games were played
+    cancelled: bool,
+    /// How the games were played, one entry per merged run
+    runs: Vec<RunMetadata>,
+}
+
+impl Results {
+    fn get(&self, strategy: &str) -> Option<&ResultSet> {
+        self.strategies
+            .iter()
+            .find(|(name, _)| name == strategy)
+            .map(|(_, set)| set)
+    }
+
+    /// Like [Results::get], but only if some of the games were decided.
+    fn get_decided(&self, strategy: &str) -> Option<&ResultSet> {
+        self.get(strategy).filter(|set| set.decided() > 0)
+    }
+
+    fn index_of(&mut self, strategy: String) -> usize {
+        match self
+            .strategies
+            .iter()
+            .position(|(name, _)| *name == strategy)
+        {
+            Some(index) => index,
+            None => {
+                self.strategies.push((strategy, ResultSet::default()));
+                self.strategies.len() - 1
+            }
+        }
+    }
+
+    /// Test whether the win rates of the strategies match the `expected` ones
+    /// using Pearson's chi-squared goodness-of-fit test, returning the statistic
+    /// and the p-value. Strategies without an expected win rate or
+    /// without decided games are left out, and `None` is returned if that
+    /// leaves none of them.
+    ///
+    /// Voided games are not counted.
+    ///
+    /// ```rust
+    /// use monty_pyrs::{GameConfig, Player, play_threaded};
+    ///
+    /// let results = play_threaded(&GameConfig::default(), &Player::CLASSIC, 1_000_000).unwrap();
+    /// let classic = [("always_switch", 2. / 3.), ("always_stay", 1. / 3.)];
+    /// let (_, p_value) = results.chi_squared_test(&classic).unwrap().unwrap();
+    /// assert!(p_value > 0.001);
+    ///
+    /// let coin_flip = [("always_switch", 0.5), ("always_stay", 0.5)];
+    /// let (_, p_value) = results.chi_squared_test(&coin_flip).unwrap().unwrap();
+    /// assert!(p_value < 1e-10);
+    /// ```
+    pub fn chi_squared_test(
+        &self,
+        expected: &[(&str, f64)],
+    ) -> Result<Option<(f64, f64)>, MontyError> {
+        for (name, rate) in expected {
+            check_probability(*rate, || format!("the expected win rate of {}", name))?;
+        }
+        let mut statistic = 0.;
+        let mut degrees = 0;
+        for (name, set) in &self.strategies {
+            let rate = match expected.iter().find(|(expected, _)| expected == name) {
+                Some((_, rate)) if set.decided() > 0 => *rate,
+                _ => continue,
+            };
+            let decided = set.decided() as f64;
+            let (wins, losses) = (decided * rate, decided * (1. - rate));
+            statistic += (set.wins as f64 - wins).powi(2) / wins
+                + (set.losses as f64 - losses).powi(2) / losses;
+            degrees += 1;
+        }
+        if degrees == 0 {
+            return Ok(None);
+        }
+        Ok(Some((statistic, stats::chi_squared_sf(statistic, degrees))))
+    }
+
+    /// The game the results were played with, if it was played by a [Simulator]
+    /// or [MontyHall] with a built-in host.
+    fn game(&self) -> Option<GameConfig> {
+        let run = self.runs.first()?;
+        let config = GameConfig::new(run.doors(), run.opened_by_host()).ok()?;
+        Some(config.with_host(run.host().parse().ok()?))
+    }
+
+    /// The exact probability of the strategy winning the game that was played,
+    /// as calculated by [exact_win_rate], if there is one.
+    pub fn exact_win_rate(&self, strategy: &str) -> Option<BigRational> {
+        exact_win_rate(&self.game()?, &strategy.parse().ok()?)
+    }
+
+    /// How the games were played, one entry per run merged into these results.
+    pub fn metadata(&self) -> &[RunMetadata] {
+        &self.runs
+    }
+
+    /// Add the results of another run of the same game, eg. played on another
+    /// machine. Fails if the runs played different games or share a seed, as
+    /// the same games would be counted twice.
+    pub fn merge(&mut self, other: Results) -> Result<(), MontyError> {
+        for run in &other.runs {
+            for existing in &self.runs {
+                if !existing.same_game(run) {
+                    return Err(MontyError::IncompatibleRuns(
+                        existing.to_string(),
+                        run.to_string(),
+                    ));
+                }
+                if let Some(seed) = run.seed().filter(|&seed| existing.seed() == Some(seed)) {
+                    return Err(MontyError::DuplicateSeed(seed));
+                }
+            }
+        }
+        *self += other;
+        Ok(())
+    }
+
+    /// Save the results as JSON.
+    pub fn to_json(&self) -> Result<String, MontyError> {
+        serde_json::to_string(self).map_err(|err| MontyError::Serialization(err.to_string()))
+    }
+
+    /// Save the results in a compact binary form, to be loaded with [Results::from_bytes].
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MontyError> {
+        bincode::serialize(self).map_err(|err| MontyError::Serialization(err.to_string()))
+    }
+}
+
+/// One line per strategy, eg. `always_switch: 66.67% won (2 won, 1 lost, 0 voided)`.
+impl fmt::Display for Results {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, (name, set)) in self.strategies.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}: {}", name, set)?;
+        }
+        if self.cancelled {
+            f.write_str("\n(incomplete)")?;
+        }
+        if self.is_aggregate() {
+            f.write_str("\n(sampled from the exact distribution, not played)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Merges the results of another run, adding up the counts of strategies with the same name.
+///
+/// Unlike [Results::merge], this does not check that the runs are compatible.
+impl std::ops::AddAssign for Results {
+    fn add_assign(&mut self, other: Self) {
+        for (name, set) in other.strategies {
+            let index = self.index_of(name);
+            self.strategies[index].1 += set;
+        }
+        self.cancelled |= other.cancelled;
+        self.runs.extend(other.runs);
+    }
 }
 
 #[pymethods]
 impl Results {
-    /// Calculate win rates for the two strategies as percentages.
+    /// Calculate win rates for the always-switch and always-stay strategies as percentages.
+    /// Fails if either has no decided games.
+    ///
+    /// Voided games are not counted, so with hosts that can reveal the car
+    /// these are the win rates conditioned on the car not being revealed.
+    ///
+    /// ```rust
+    /// use monty_pyrs::{exact_win_rate, GameConfig, Player, Results, play_threaded};
+    /// use assert_approx_eq::assert_approx_eq;
+    /// use num_traits::ToPrimitive;
+    ///
+    /// let config = GameConfig::default();
+    /// let results: Results = play_threaded(&config, &Player::CLASSIC, 1_000_000).unwrap();
+    /// let (switched_pct, stayed_pct) = results.calc_win_rate().unwrap();
+    /// let exact = |player| exact_win_rate(&config, &player).unwrap().to_f64().unwrap();
+    /// // Ensure we are within 0.5 of the exact percentage
+    /// assert_approx_eq!(switched_pct, exact(Player::AlwaysSwitch), 0.005);
+    /// assert_approx_eq!(stayed_pct, exact(Player::AlwaysStay), 0.005);
+    ///
+    /// // Without games there are no win rates to calculate
+    /// assert!(Results::default().calc_win_rate().is_err());
+    /// ```
+    pub fn calc_win_rate(&self) -> Result<(f64, f64), MontyError> {
+        let win_rate = |strategy: &str| {
+            self.win_rate(strategy)
+                .ok_or_else(|| MontyError::NoDecidedGames(strategy.to_string()))
+        };
+        Ok((win_rate("always_switch")?, win_rate("always_stay")?))
+    }
+
+    /// Calculate the share of games voided because the host revealed the car,
+    /// for the always-switch and always-stay strategies.
     ///
     /// ```rust
-    /// use monty_pyrs::{Results, play_threaded};
+    /// use monty_pyrs::{GameConfig, Host, Player, Results, play_threaded};
     /// use assert_approx_eq::assert_approx_eq;
     ///
-    /// let results: Results = play_threaded(1_000_000);
-    /// let (switched_pct, stayed_pct) = results.calc_win_rate();
-    /// // Ensure we are within 0.5 of target percentage
-    /// assert_approx_eq!(switched_pct, 0.6667, 0.005);
-    /// assert_approx_eq!(stayed_pct, 0.3333, 0.005);
+    /// let config = GameConfig::default().with_host(Host::Ignorant);
+    /// let results: Results = play_threaded(&config, &Player::CLASSIC, 1_000_000).unwrap();
+    /// let (switched_void, stayed_void) = results.calc_void_rate().unwrap();
+    /// assert_approx_eq!(switched_void, 0.3333, 0.005);
+    /// assert_approx_eq!(stayed_void, 0.3333, 0.005);
+    ///
+    /// // Once a goat has been revealed by chance, switching no longer helps
+    /// let (switched_pct, stayed_pct) = results.calc_win_rate().unwrap();
+    /// assert_approx_eq!(switched_pct, 0.5, 0.005);
+    /// assert_approx_eq!(stayed_pct, 0.5, 0.005);
     /// ```
-    pub fn calc_win_rate(&self) -> (f64, f64) {
-        (
-            self.switched.wins as f64 / (self.switched.wins + self.switched.losses) as f64,
-            self.stayed.wins as f64 / (self.stayed.wins + self.stayed.losses) as f64,
+    pub fn calc_void_rate(&self) -> Result<(f64, f64), MontyError> {
+        let void_rate = |strategy: &str| {
+            self.get(strategy)
+                .and_then(ResultSet::void_rate)
+                .ok_or_else(|| MontyError::NoGamesPlayed(strategy.to_string()))
+        };
+        Ok((void_rate("always_switch")?, void_rate("always_stay")?))
+    }
+
+    /// The names of the strategies played, in the order they were first played.
+    pub fn strategies(&self) -> Vec<String> {
+        self.strategies
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Calculate the win rate of a single strategy, if any of its games were decided.
+    pub fn win_rate(&self, strategy: &str) -> Option<f64> {
+        self.get(strategy).and_then(ResultSet::win_rate)
+    }
+
+    /// Calculate the win rates of all strategies played, or `None` for
+    /// strategies without decided games.
+    pub fn win_rates(&self) -> Vec<(String, Option<f64>)> {
+        self.strategies
+            .iter()
+            .map(|(name, set)| (name.clone(), set.win_rate()))
+            .collect()
+    }
+
+    /// The number of games played with a single strategy, including voided games.
+    pub fn games(&self, strategy: &str) -> Option<u64> {
+        self.get(strategy).map(ResultSet::games)
+    }
+
+    /// The number of games played across all strategies, including voided games.
+    ///
+    /// ```rust
+    /// use monty_pyrs::{GameConfig, MontyHall, Player};
+    ///
+    /// let mut monty = MontyHall::default();
+    /// let players = [Player::AlwaysSwitch, Player::AlwaysStay, Player::RandomSwitch(0.5)];
+    /// let results = monty.play_multiple(&GameConfig::default(), &players, 7).unwrap();
+    /// assert_eq!(results.total_games(), 7);
+    /// assert_eq!(results.games("always_switch"), Some(3));
+    /// assert_eq!(results.games("random_switch(0.5)"), Some(2));
+    /// ```
+    pub fn total_games(&self) -> u64 {
+        self.strategies.iter().map(|(_, set)| set.games()).sum()
+    }
+
+    /// Whether all requested games were played. Runs that were cancelled
+    /// return the results of the games played so far.
+    pub fn is_complete(&self) -> bool {
+        !self.cancelled
+    }
+
+    /// Whether any of the merged runs was sampled from the exact distribution
+    /// with [MontyHall::play_aggregate] rather than played game by game.
+    pub fn is_aggregate(&self) -> bool {
+        self.runs.iter().any(RunMetadata::is_aggregate)
+    }
+
+    /// The counters of a single strategy, if it was played.
+    ///
+    /// ```rust
+    /// use monty_pyrs::{GameConfig, MontyHall, Player};
+    ///
+    /// let mut monty = MontyHall::default();
+    /// let results = monty.play_multiple(&GameConfig::default(), &Player::CLASSIC, 1_000).unwrap();
+    /// let switched = results.result_set("always_switch").unwrap();
+    /// assert_eq!(switched.wins() + switched.losses() + switched.voided(), 500);
+    /// assert_eq!(switched.win_rate(), results.win_rate("always_switch"));
+    /// ```
+    pub fn result_set(&self, strategy: &str) -> Option<ResultSet> {
+        self.get(strategy).cloned()
+    }
+
+    /// The counters of all strategies played, in the order they were first played.
+    pub fn result_sets(&self) -> Vec<(String, ResultSet)> {
+        self.strategies.clone()
+    }
+
+    /// The counters and rates of every strategy as a dict of dicts, keyed by
+    /// strategy name. Load it into pandas with
+    /// `pandas.DataFrame.from_dict(results.to_dict(), orient="index")`.
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let dict = PyDict::new(py);
+        for (name, set) in &self.strategies {
+            dict.set_item(name, set.to_dict(py)?)?;
+        }
+        Ok(dict)
+    }
+
+    /// Create empty results, eg. to merge others into with `+=`.
+    #[new]
+    fn py_new() -> Self {
+        Self::default()
+    }
+
+    fn __repr__(&self) -> String {
+        let strategies: Vec<String> = self
+            .strategies
+            .iter()
+            .map(|(name, set)| format!("{:?}: {}", name, set.__repr__()))
+            .collect();
+        format!(
+            "Results({{{}}}, complete={})",
+            strategies.join(", "),
+            if self.cancelled { "False" } else { "True" }
         )
     }
+
+    fn __str__(&self) -> String {
+        self.to_string()
+    }
+
+    fn __len__(&self) -> usize {
+        self.strategies.len()
+    }
+
+    fn __contains__(&self, strategy: &str) -> bool {
+        self.get(strategy).is_some()
+    }
+
+    fn __getitem__(&self, strategy: &str) -> PyResult<ResultSet> {
+        self.result_set(strategy)
+            .ok_or_else(|| PyKeyError::new_err(strategy.to_string()))
+    }
+
+    fn __richcmp__(&self, other: PyRef<Self>, op: CompareOp, py: Python) -> PyObject {
+        match op {
+            CompareOp::Eq => (*self == *other).into_py(py),
+            CompareOp::Ne => (*self != *other).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    /// Merges like [Results::merge], raising `ValueError` for incompatible runs.
+    fn __add__(&self, other: PyRef<Self>) -> Result<Self, MontyError> {
+        let mut sum = self.clone();
+        sum.merge(other.clone())?;
+        Ok(sum)
+    }
+
+    fn __iadd__(&mut self, other: Self) -> Result<(), MontyError> {
+        self.merge(other)
+    }
+
+    // Without `py`: pickle calls this with a null argument array, which pyo3
+    // reads when the method takes any arguments
+    fn __getstate__(&self) -> Result<Vec<u8>, MontyError> {
+        self.to_bytes()
+    }
+
+    fn __setstate__(&mut self, state: Vec<u8>) -> Result<(), MontyError> {
+        *self = Self::from_bytes(&state)?;
+        Ok(())
+    }
+
+    /// Python's [Results::merge].
+    #[pyo3(name = "merge")]
+    fn py_merge(&mut self, other: Self) -> Result<(), MontyError> {
+        self.merge(other)
+    }
+
+    /// Python's [Results::metadata], as a list of dicts.
+    #[pyo3(name = "metadata")]
+    fn py_metadata<'py>(&self, py: Python<'py>) -> PyResult<Vec<&'py PyDict>> {
+        self.runs
+            .iter()
+            .map(|run| {
+                let dict = PyDict::new(py);
+                dict.set_item("version", run.version())?;
+                dict.set_item("doors", run.doors())?;
+                dict.set_item("opened_by_host", run.opened_by_host())?;
+                dict.set_item("host", run.host())?;
+                dict.set_item("fidelity", run.fidelity().to_string())?;
+                dict.set_item("seed", run.seed())?;
+                dict.set_item("rng", run.rng().map(|rng| rng.to_string()))?;
+                dict.set_item("threads", run.threads())?;
+                dict.set_item("aggregate", run.is_aggregate())?;
+                Ok(dict)
+            })
+            .collect()
+    }
+
+    /// Python's [Results::to_json].
+    #[pyo3(name = "to_json")]
+    fn py_to_json(&self) -> Result<String, MontyError> {
+        self.to_json()
+    }
+
+    /// Load results saved with [Results::to_json].
+    #[staticmethod]
+    pub fn from_json(json: &str) -> Result<Self, MontyError> {
+        serde_json::from_str(json).map_err(|err| MontyError::Serialization(err.to_string()))
+    }
+
+    /// Python's [Results::to_bytes], returning `bytes`.
+    #[pyo3(name = "to_bytes")]
+    fn py_to_bytes<'py>(&self, py: Python<'py>) -> PyResult<&'py PyBytes> {
+        Ok(PyBytes::new(py, &self.to_bytes()?))
+    }
+
+    /// Load results saved with [Results::to_bytes].
+    #[staticmethod]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MontyError> {
+        bincode::deserialize(bytes).map_err(|err| MontyError::Serialization(err.to_string()))
+    }
+
+    /// The Wilson score interval for the win rate of a strategy at the given
+    /// confidence level, if any of its games were decided. Python's `level` defaults to 0.95.
+    ///
+    /// ```rust
+    /// use monty_pyrs::{GameConfig, Player, play_threaded};
+    ///
+    /// let results = play_threaded(&GameConfig::default(), &Player::CLASSIC, 1_000_000).unwrap();
+    /// let (lower, upper) = results.wilson_interval("always_switch", 0.999).unwrap().unwrap();
+    /// assert!(lower < 2. / 3. && 2. / 3. < upper);
+    /// assert!(upper - lower < 0.01);
+    /// assert!(results.wilson_interval("always_switch", 1.).is_err());
+    /// ```
+    #[args(level = "0.95")]
+    pub fn wilson_interval(
+        &self,
+        strategy: &str,
+        level: f64,
+    ) -> Result<Option<(f64, f64)>, MontyError> {
+        check_probability(level, || "the confidence level".to_string())?;
+        Ok(self
+            .get_decided(strategy)
+            .map(|set| stats::wilson_interval(set.wins, set.decided(), level)))
+    }
+
+    /// The exact Clopper-Pearson interval for the win rate of a strategy at the
+    /// given confidence level, if any of its games were decided. It is more conservative than
+    /// [Results::wilson_interval]. Python's `level` defaults to 0.95.
+    ///
+    /// ```rust
+    /// use monty_pyrs::{GameConfig, Player, play_threaded};
+    ///
+    /// let results = play_threaded(&GameConfig::default(), &Player::CLASSIC, 1_000_000).unwrap();
+    /// let (lower, upper) = results.clopper_pearson_interval("always_stay", 0.999).unwrap().unwrap();
+    /// assert!(lower < 1. / 3. && 1. / 3. < upper);
+    /// assert!(upper - lower < 0.01);
+    /// ```
+    #[args(level = "0.95")]
+    pub fn clopper_pearson_interval(
+        &self,
+        strategy: &str,
+        level: f64,
+    ) -> Result<Option<(f64, f64)>, MontyError> {
+        check_probability(level, || "the confidence level".to_string())?;
+        Ok(self
+            .get_decided(strategy)
+            .map(|set| stats::clopper_pearson_interval(set.wins, set.decided(), level)))
+    }
+
+    /// Compare the win rates of two strategies with a pooled two-proportion
+    /// z-test, returning the z statistic and the two-sided p-value, if both
+    /// have decided games. Python compares always switching with always staying by default.
+    ///
+    /// ```rust
+    /// use monty_pyrs::{GameConfig, Host, Player, play_threaded};
+    ///
+    /// let results = play_threaded(&GameConfig::default(), &Player::CLASSIC, 1_000_000).unwrap();
+    /// let (z, p_value) = results.z_test("always_switch", "always_stay").unwrap();
+    /// assert!(z > 0.);
+    /// assert!(p_value < 1e-10);
+    ///
+    /// // Switching never wins against the devilish host, however it's done
+    /// let config = GameConfig::default().with_host(Host::Devilish);
+    /// let players = [Player::AlwaysSwitch, Player::RandomSwitch(1.), Player::AlwaysStay];
+    /// let results = play_threaded(&config, &players, 30_000).unwrap();
+    /// assert_eq!(results.z_test("always_switch", "random_switch(1)"), Some((0., 1.)));
+    /// assert_eq!(results.fisher_exact_test("always_switch", "random_switch(1)"), Some((1., 1.)));
+    /// let (odds_ratio, p_value) = results.fisher_exact_test("always_stay", "always_switch").unwrap();
+    /// assert_eq!(odds_ratio, f64::INFINITY);
+    /// assert!(p_value < 1e-10);
+    /// ```
+    #[args(first = "\"always_switch\"", second = "\"always_stay\"")]
+    pub fn z_test(&self, first: &str, second: &str) -> Option<(f64, f64)> {
+        let (first, second) = (self.get_decided(first)?, self.get_decided(second)?);
+        Some(stats::two_proportion_z_test(
+            first.wins,
+            first.decided(),
+            second.wins,
+            second.decided(),
+        ))
+    }
+
+    /// Compare the win rates of two strategies with Fisher's exact test,
+    /// returning the odds ratio and the two-sided p-value, if both have decided games.
+    /// Python compares always switching with always staying by default.
+    ///
+    /// ```rust
+    /// use monty_pyrs::{GameConfig, MontyHall, Player, play_threaded};
+    ///
+    /// let players = [Player::AlwaysSwitch, Player::RandomSwitch(1.)];
+    /// let results = play_threaded(&GameConfig::default(), &players, 1_000_000).unwrap();
+    /// // Two ways of always switching aren't told apart
+    /// let (_, p_value) = results.fisher_exact_test("always_switch", "random_switch(1)").unwrap();
+    /// assert!(p_value > 0.001);
+    ///
+    /// // Nor are they with billions of games, where it agrees with the z-test
+    /// let results = MontyHall::seeded(2).play_aggregate(&GameConfig::default(), &players, 10_000_000_000).unwrap();
+    /// let (_, p_value) = results.fisher_exact_test("always_switch", "random_switch(1)").unwrap();
+    /// let (_, z_p_value) = results.z_test("always_switch", "random_switch(1)").unwrap();
+    /// assert!((p_value - z_p_value).abs() < 0.01, "{} and {}", p_value, z_p_value);
+    /// ```
+    #[args(first = "\"always_switch\"", second = "\"always_stay\"")]
+    pub fn fisher_exact_test(&self, first: &str, second: &str) -> Option<(f64, f64)> {
+        let (first, second) = (self.get_decided(first)?, self.get_decided(second)?);
+        Some(stats::fisher_exact_test(
+            first.wins,
+            first.losses,
+            second.wins,
+            second.losses,
+        ))
+    }
+
+    /// How far the simulated win rate of a strategy is off from the exact
+    /// probability given by [Results::exact_win_rate], if both are known.
+    ///
+    /// ```rust
+    /// use monty_pyrs::{GameConfig, Host, Player, play_threaded};
+    ///
+    /// let config = GameConfig::new(5, 2).unwrap().with_host(Host::MontyFall);
+    /// let results = play_threaded(&config, &Player::CLASSIC, 1_000_000).unwrap();
+    /// assert!(results.deviation("always_switch").unwrap().abs() < 0.005);
+    /// ```
+    pub fn deviation(&self, strategy: &str) -> Option<f64> {
+        Some(self.win_rate(strategy)? - self.exact_win_rate(strategy)?.to_f64()?)
+    }
+
+    /// The [Results::deviation] of every strategy played.
+    pub fn deviations(&self) -> Vec<(String, Option<f64>)> {
+        self.strategies
+            .iter()
+            .map(|(name, _)| (name.clone(), self.deviation(name)))
+            .collect()
+    }
+
+    /// Python's [Results::exact_win_rate], as a `fractions.Fraction`.
+    #[pyo3(name = "exact_win_rate")]
+    fn py_exact_win_rate(&self, py: Python, strategy: &str) -> PyResult<Option<PyObject>> {
+        self.exact_win_rate(strategy)
+            .map(|rate| python::to_fraction(py, &rate))
+            .transpose()
+    }
+
+    /// Python's [Results::chi_squared_test], taking a dict of expected win
+    /// rates that defaults to the classic 2/3 for switching and 1/3 for staying.
+    #[pyo3(name = "chi_squared_test")]
+    fn py_chi_squared_test(
+        &self,
+        expected: Option<HashMap<String, f64>>,
+    ) -> Result<Option<(f64, f64)>, MontyError> {
+        match expected {
+            Some(expected) => {
+                let expected: Vec<(&str, f64)> = expected
+                    .iter()
+                    .map(|(name, rate)| (name.as_str(), *rate))
+                    .collect();
+                self.chi_squared_test(&expected)
+            }
+            None => self.chi_squared_test(&CLASSIC_WIN_RATES),
+        }
+    }
+}
+
+/// How a game played out, for [MontyHall::play_single] and [MontyHall::play_traced].
+struct Game {
+    car: u32,
+    initial_choice: u32,
+    host_action: HostAction,
+    switched: bool,
+    final_choice: u32,
+    outcome: Outcome,
 }
 
 /// Holds the RNG for generating a random correct door
 /// as well as the impl for playing games.
 pub struct MontyHall<R> {
     rng: R,
+    /// Scratch space for the doors left closed by the host, reused between games
+    closed: Vec<u32>,
+    /// Scratch space for the doors opened by the host, reused between games
+    opened: Vec<u32>,
+    fidelity: Fidelity,
+    /// Resolved, never [Backend::Auto]
+    backend: Backend,
+    /// The built-in generator `rng` is, if known
+    rng_kind: Option<RngKind>,
+    /// The seed of `rng`, until the first run recorded it
+    seed: Option<u64>,
+    cancellation: Option<CancellationToken>,
 }
 
 impl<R> MontyHall<R>
@@ -74,136 +1127,561 @@ where
     R: RngCore,
 {
     /// Allows you to BYO random generator.
+    ///
+    /// The games are played with the generator itself, on [Backend::Scalar]:
+    /// the vectorized backends would only seed their own generators from it.
     /// ```rust
-    /// use monty_pyrs::MontyHall;
+    /// use monty_pyrs::{Backend, GameConfig, MontyHall, Player};
     /// use rand_xorshift::XorShiftRng;
     /// use rand_core::SeedableRng;
     ///
     /// let rng = XorShiftRng::seed_from_u64(1337);
     /// let mut monty = MontyHall::new_with_rng(rng);
-    /// let success = monty.play_single(true);
+    /// let outcome = monty.play_single(&GameConfig::default(), &Player::AlwaysSwitch);
+    /// assert_eq!(monty.backend(), Backend::Scalar);
     /// ```
     pub fn new_with_rng(rng: R) -> Self {
-        Self { rng }
+        Self {
+            rng,
+            closed: Vec::new(),
+            opened: Vec::new(),
+            fidelity: Fidelity::default(),
+            backend: Backend::Scalar,
+            rng_kind: None,
+            seed: None,
+            cancellation: None,
+        }
     }
 
-    /// Play a single simulation of the Monty Hall problem.
+    /// Mark `rng` as the built-in generator `kind`, playing on the backend a
+    /// [Simulator] with that generator would use.
+    fn with_rng_kind(mut self, kind: RngKind) -> Self {
+        self.rng_kind = Some(kind);
+        self.backend = Backend::Auto.resolve_for(kind).unwrap_or(Backend::Scalar);
+        self
+    }
+
+    /// Record `seed` as the seed of `rng` in the metadata of the first run.
+    fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Choose how faithfully games are simulated. Defaults to [Fidelity::Fast].
+    ///
+    /// The shortcuts skew the results of strategies that depend on which
+    /// doors the host opened:
     /// ```rust
-    /// use monty_pyrs::MontyHall;
+    /// use monty_pyrs::{Fidelity, GameConfig, MontyHall, Player};
+    /// use assert_approx_eq::assert_approx_eq;
+    ///
+    /// let config = GameConfig::default();
+    /// let players = [Player::SwitchIfHostOpened(1)];
     ///
     /// let mut monty = MontyHall::default();
-    /// let success = monty.play_single(true);
+    /// let results = monty.play_multiple(&config, &players, 1_000_000).unwrap();
+    /// assert_approx_eq!(results.win_rate("switch_if_opened(1)").unwrap(), 1. / 3., 0.005);
+    ///
+    /// let mut monty = MontyHall::default().with_fidelity(Fidelity::Faithful);
+    /// let results = monty.play_multiple(&config, &players, 1_000_000).unwrap();
+    /// assert_approx_eq!(results.win_rate("switch_if_opened(1)").unwrap(), 4. / 9., 0.005);
     /// ```
-    pub fn play_single(&mut self, switch_doors: bool) -> bool {
-        let mut doors: ArrayVec<[i8; 3]> = array_vec![0, 1, 2];
-        let correct_door = (self.rng.next_u32() % 3) as i8;
-        let mut choice: i8 = 0; // https://xkcd.com/221/, sort of
+    pub fn with_fidelity(mut self, fidelity: Fidelity) -> Self {
+        self.fidelity = fidelity;
+        self
+    }
 
-        // Find the first non-correct, non-chosen door and remove it
-        doors
-            .iter()
-            .position(|&x| x != correct_door && x != choice)
-            .map(|e| doors.remove(e));
+    /// Choose how the games covered by the bit-parallel kernel are played.
+    /// Defaults to [Backend::Auto], or to [Backend::Scalar] for generators
+    /// passed to [MontyHall::new_with_rng].
+    ///
+    /// [Backend::Auto] only picks a vectorized backend for
+    /// [RngKind::XorShift]. Fails if the CPU doesn't support the backend, or
+    /// it is vectorized and the generator is another built-in one.
+    ///
+    /// ```rust
+    /// use monty_pyrs::{Backend, MontyHall, RngKind};
+    ///
+    /// let monty = MontyHall::default().with_backend(Backend::Scalar).unwrap();
+    /// assert_eq!(monty.backend(), Backend::Scalar);
+    /// let monty = MontyHall::default().with_backend(Backend::Auto).unwrap();
+    /// assert_eq!(monty.backend(), Backend::detect());
+    ///
+    /// let monty = MontyHall::seeded_with(RngKind::ChaCha20, 1);
+    /// assert_eq!(monty.with_backend(Backend::Auto).unwrap().backend(), Backend::Scalar);
+    /// assert!(MontyHall::seeded_with(RngKind::ChaCha20, 1).with_backend(Backend::Portable).is_err());
+    /// ```
+    pub fn with_backend(mut self, backend: Backend) -> Result<Self, MontyError> {
+        self.backend = match self.rng_kind {
+            Some(kind) => backend.resolve_for(kind)?,
+            None if backend == Backend::Auto => Backend::Scalar,
+            None => backend.resolve()?,
+        };
+        Ok(self)
+    }
 
-        if switch_doors {
-            // Unwrapping is safe; we know there will always be at least one viable option left
-            choice = *doors.iter().find(|&&x| x != choice).unwrap();
+    /// The backend playing the games covered by the bit-parallel kernel,
+    /// with [Backend::Auto] resolved to the detected one.
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    /// Stop playing once `token` is cancelled.
+    ///
+    /// [MontyHall::play_multiple] checks the token every
+    /// [CANCELLATION_CHECK_INTERVAL] games and returns the results played so
+    /// far, marked as incomplete.
+    /// ```rust
+    /// use monty_pyrs::{CancellationToken, GameConfig, MontyHall, Player};
+    ///
+    /// let token = CancellationToken::new();
+    /// let mut monty = MontyHall::default().with_cancellation(token.clone());
+    /// token.cancel();
+    /// let results = monty.play_multiple(&GameConfig::default(), &Player::CLASSIC, 1_000_000).unwrap();
+    /// assert!(!results.is_complete());
+    /// assert_eq!(results.total_games(), 0);
+    /// ```
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
+
+    /// Play a single simulation of the Monty Hall problem.
+    ///
+    /// When switching, the new door is picked at random from the doors the host
+    /// left closed. In the classic game there is only one such door.
+    /// ```rust
+    /// use monty_pyrs::{GameConfig, MontyHall, Outcome, Player};
+    ///
+    /// let mut monty = MontyHall::default();
+    /// let outcome = monty.play_single(&GameConfig::default(), &Player::AlwaysSwitch);
+    ///
+    /// // With 100 doors of which the host opens 98, switching almost always wins
+    /// let config = GameConfig::new(100, 98).unwrap();
+    /// let wins = (0..1000)
+    ///     .filter(|_| monty.play_single(&config, &Player::AlwaysSwitch) == Outcome::Won)
+    ///     .count();
+    /// assert!(wins > 950);
+    /// ```
+    pub fn play_single<P>(&mut self, config: &GameConfig, player: &P) -> Outcome
+    where
+        P: PlayerStrategy + ?Sized,
+    {
+        self.play_game(config, player).outcome
+    }
+
+    /// Like [MontyHall::play_single], but records how the game played out.
+    /// Plays the same game as [MontyHall::play_single] would have.
+    ///
+    /// ```rust
+    /// use monty_pyrs::{GameConfig, MontyHall, Player};
+    ///
+    /// let config = GameConfig::default();
+    /// let mut traced = MontyHall::default();
+    /// let mut untraced = MontyHall::default();
+    /// for _ in 0..1000 {
+    ///     let record = traced.play_traced(&config, &Player::AlwaysSwitch);
+    ///     assert_eq!(record.outcome(), untraced.play_single(&config, &Player::AlwaysSwitch));
+    /// }
+    /// ```
+    pub fn play_traced<P>(&mut self, config: &GameConfig, player: &P) -> GameRecord
+    where
+        P: PlayerStrategy + ?Sized,
+    {
+        let game = self.play_game(config, player);
+        GameRecord {
+            strategy: player.name(),
+            car: game.car,
+            initial_choice: game.initial_choice,
+            opened: self.opened.clone(),
+            host_action: game.host_action,
+            switched: game.switched,
+            final_choice: game.final_choice,
+            outcome: game.outcome,
         }
+    }
+
+    /// Trace `iterations` games, handing them out to the strategies in turn
+    /// like [MontyHall::play_multiple]. The games are played as the iterator
+    /// is advanced.
+    ///
+    /// Fails if there are fewer iterations than strategies.
+    pub fn trace<'a>(
+        &'a mut self,
+        config: &'a GameConfig,
+        players: &'a [Player],
+        iterations: u64,
+    ) -> Result<Trace<'a, R>, MontyError> {
+        check_iterations(players, iterations)?;
+        Ok(Trace::new(self, config, players, iterations))
+    }
 
-        choice == correct_door
+    /// Play a game, leaving the doors opened by the host in `self.opened`.
+    // Inlined so that untraced games skip recording what they don't need
+    #[inline(always)]
+    fn play_game<P>(&mut self, config: &GameConfig, player: &P) -> Game
+    where
+        P: PlayerStrategy + ?Sized,
+    {
+        let correct_door = uniform_below(&mut self.rng, config.doors);
+        let initial_choice = match player.initial_pick(&mut self.rng, config.doors) {
+            Some(pick) => pick,
+            None if self.fidelity == Fidelity::Faithful => {
+                uniform_below(&mut self.rng, config.doors)
+            }
+            None => 0, // https://xkcd.com/221/, sort of
+        };
+        debug_assert!(
+            initial_choice < config.doors,
+            "picked a door that doesn't exist"
+        );
+        let mut choice = initial_choice;
+
+        // Let the host open doors among the ones that weren't chosen
+        self.closed.clear();
+        self.opened.clear();
+        self.closed
+            .extend((0..config.doors).filter(|&x| x != choice));
+        let mut round = Round::new(
+            correct_door,
+            choice,
+            config.opened_by_host,
+            self.fidelity,
+            &mut self.closed,
+            &mut self.opened,
+        );
+        let action = config.host.open_doors(&mut self.rng, &mut round);
+        // Custom hosts can break the rules, eg. by opening every door, and
+        // such games don't count
+        let action = if round.to_open() > 0 || round.closed().is_empty() {
+            HostAction::Void
+        } else {
+            action
+        };
+
+        let reveal = Reveal {
+            choice,
+            opened: &self.opened,
+            closed: &self.closed,
+        };
+        let switched = match action {
+            HostAction::Offer => player.switch(&mut self.rng, &reveal),
+            HostAction::NoOffer | HostAction::Void => false,
+        };
+        if switched {
+            // Indexing is safe; games without a door left to switch to are voided
+            choice = match self.closed.len() {
+                1 => self.closed[0],
+                len => self.closed[uniform_below(&mut self.rng, len as u32) as usize],
+            };
+        }
+        let outcome = match action {
+            HostAction::Void => Outcome::Voided,
+            _ if choice == correct_door => Outcome::Won,
+            _ => Outcome::Lost,
+        };
+        Game {
+            car: correct_door,
+            initial_choice,
+            host_action: action,
+            switched,
+            final_choice: choice,
+            outcome,
+        }
     }
 
     /// Play a number of simulations.
     ///
-    /// Half of the simulations use the switching strategy, the other half do not.
+    /// The simulations are split evenly between the given strategies. When
+    /// they can't be split evenly, the first strategies play one extra game.
+    ///
+    /// Fails if there are fewer iterations than strategies, as some of them
+    /// wouldn't get to play.
+    ///
+    /// The first run of a [MontyHall::seeded] one records its seed, so the
+    /// results can't be merged with another run of the same games. Later runs
+    /// continue from where the generator left off and play other games.
     ///
     /// ```rust
-    /// use monty_pyrs::{MontyHall, Results};
+    /// use monty_pyrs::{GameConfig, MontyHall, Player, Results};
     ///
     /// let mut monty = MontyHall::default();
-    /// let results: Results = monty.play_multiple(1_000_000);
+    /// let results: Results = monty.play_multiple(&GameConfig::default(), &Player::CLASSIC, 1_000_001).unwrap();
+    /// assert_eq!(results.strategies(), ["always_switch", "always_stay"]);
+    /// assert_eq!(results.total_games(), 1_000_001);
+    /// assert!(monty.play_multiple(&GameConfig::default(), &Player::CLASSIC, 1).is_err());
+    ///
+    /// let mut monty = MontyHall::seeded(1);
+    /// let mut results = monty.play_multiple(&GameConfig::default(), &Player::CLASSIC, 1000).unwrap();
+    /// let again = MontyHall::seeded(1).play_multiple(&GameConfig::default(), &Player::CLASSIC, 1000).unwrap();
+    /// assert_eq!(results.metadata()[0].seed(), Some(1));
+    /// assert!(results.merge(again).is_err());
+    /// let more = monty.play_multiple(&GameConfig::default(), &Player::CLASSIC, 1000).unwrap();
+    /// assert!(results.merge(more).is_ok());
+    /// ```
+    pub fn play_multiple(
+        &mut self,
+        config: &GameConfig,
+        players: &[Player],
+        iterations: u64,
+    ) -> Result<Results, MontyError> {
+        check_iterations(players, iterations)?;
+        let mut results = self.play_range(config, players, 0..iterations);
+        results.runs = vec![self.run_metadata(config)];
+        Ok(results)
+    }
+
+    /// Like [MontyHall::play_multiple], but rather than playing the games,
+    /// sample how many of them are won, lost and voided from their exact
+    /// distribution. This takes constant time for any number of iterations,
+    /// and the results are distributed like played ones, but they are not
+    /// the results of any particular games and are marked as
+    /// [aggregate](RunMetadata::is_aggregate).
+    ///
+    /// Fails for strategies without an [exact_win_rate].
+    ///
+    /// ```rust
+    /// use monty_pyrs::{GameConfig, MontyHall, Player};
+    ///
+    /// let results = MontyHall::seeded(1).play_aggregate(&GameConfig::default(), &Player::CLASSIC, 1_000_000_000_000).unwrap();
+    /// assert_eq!(results.games("always_switch"), Some(500_000_000_000));
+    /// assert!(results.is_aggregate());
     /// ```
-    pub fn play_multiple(&mut self, iterations: u64) -> Results {
-        let half = iterations / 2;
+    pub fn play_aggregate(
+        &mut self,
+        config: &GameConfig,
+        players: &[Player],
+        iterations: u64,
+    ) -> Result<Results, MontyError> {
+        check_iterations(players, iterations)?;
+        let mut results = aggregate::play(&mut self.rng, config, players, iterations)?;
+        results.runs = vec![self.run_metadata(config).aggregate()];
+        Ok(results)
+    }
+
+    /// Describe the next run, taking the seed so that only the first run
+    /// records it.
+    fn run_metadata(&mut self, config: &GameConfig) -> RunMetadata {
+        RunMetadata::new(config, self.fidelity, self.seed.take(), self.rng_kind, 1)
+    }
+
+    /// Play the games numbered `games` out of a larger run, handing out
+    /// game number `n` to strategy `n % players.len()` so that remainders are
+    /// split the same way no matter how the run is divided, and so that the
+    /// strategies stay balanced when the run is cancelled.
+    fn play_range(
+        &mut self,
+        config: &GameConfig,
+        players: &[Player],
+        games: Range<u64>,
+    ) -> Results {
         let mut results = Results::default();
-        for _ in 0..half {
-            let switch = true;
-            let won = self.play_single(switch);
-            if won {
-                results.switched.wins += 1;
-            } else {
-                results.switched.losses += 1;
-            }
+        if players.is_empty() {
+            return results;
+        }
+        let indices: Vec<usize> = players
+            .iter()
+            .map(|player| results.index_of(player.name()))
+            .collect();
+        let kernels: Option<Vec<Kernel>> = players
+            .iter()
+            .map(|player| Kernel::new(config, self.fidelity, player))
+            .collect();
+        if let Some(kernels) = kernels {
+            self.play_range_batched(&kernels, &indices, games, &mut results);
+            return results;
         }
-        for _ in 0..half {
-            let switch = false;
-            let won = self.play_single(switch);
-            if won {
-                results.stayed.wins += 1;
-            } else {
-                results.stayed.losses += 1;
+        let mut position = (games.start % players.len() as u64) as usize;
+        for game in games {
+            if game.is_multiple_of(CANCELLATION_CHECK_INTERVAL) && self.is_cancelled() {
+                results.cancelled = true;
+                break;
             }
+            let outcome = self.play_single(config, &players[position]);
+            results.strategies[indices[position]].1.record(outcome);
+            position = (position + 1) % players.len();
         }
         results
     }
+
+    /// Like [MontyHall::play_range], with the [Kernel] of every strategy.
+    /// Each strategy plays its share of the games between cancellation checks
+    /// in one go, on the chosen [Backend].
+    fn play_range_batched(
+        &mut self,
+        kernels: &[Kernel],
+        indices: &[usize],
+        games: Range<u64>,
+        results: &mut Results,
+    ) {
+        let strategies = kernels.len() as u64;
+        // The number of games below `game` handed out to strategy `position`
+        let handed_out = |game: u64, position: u64| (game + strategies - 1 - position) / strategies;
+        let mut lanes = match self.backend {
+            Backend::Scalar => None,
+            _ => Some(LaneRng::from_rng(&mut self.rng)),
+        };
+        let mut start = games.start;
+        while start < games.end {
+            if start.is_multiple_of(CANCELLATION_CHECK_INTERVAL) && self.is_cancelled() {
+                results.cancelled = true;
+                break;
+            }
+            let end = games
+                .end
+                .min((start / CANCELLATION_CHECK_INTERVAL + 1) * CANCELLATION_CHECK_INTERVAL);
+            for (position, kernel) in kernels.iter().enumerate() {
+                let count = handed_out(end, position as u64) - handed_out(start, position as u64);
+                results.strategies[indices[position]].1 += match &mut lanes {
+                    Some(lanes) => simd::play(self.backend, lanes, kernel, count),
+                    None => kernel.play(&mut self.rng, count),
+                };
+            }
+            start = end;
+        }
+    }
+}
+
+impl MontyHall<XorShiftRng> {
+    /// Seed the random number generator like the first chunk of games played
+    /// by a [Simulator] with the same seed, so that tracing the games with the
+    /// same [Fidelity] reproduces them. Fast games of only always switching and
+    /// always staying are the exception, as they are played in bulk rather than
+    /// one at a time.
+    ///
+    /// ```rust
+    /// use monty_pyrs::{GameConfig, MontyHall, Player, Simulator};
+    ///
+    /// let config = GameConfig::default();
+    /// let simulator = Simulator::builder().seed(3).build().unwrap();
+    /// let played = simulator.play(&config, &Player::CLASSIC, 1000).unwrap();
+    /// let traced = MontyHall::seeded(3).play_multiple(&config, &Player::CLASSIC, 1000).unwrap();
+    /// assert_eq!(traced.result_sets(), played.result_sets());
+    /// ```
+    pub fn seeded(seed: u64) -> Self {
+        Self::new_with_rng(XorShiftRng::seed_from_u64(rng::stream_seed(seed, 0)))
+            .with_rng_kind(RngKind::XorShift)
+            .with_seed(seed)
+    }
+}
+
+impl MontyHall<AnyRng> {
+    /// Like [MontyHall::seeded], for a [Simulator] playing with the `rng` generator.
+    ///
+    /// ```rust
+    /// use monty_pyrs::{GameConfig, MontyHall, Player, RngKind, Simulator};
+    ///
+    /// let config = GameConfig::default();
+    /// let simulator = Simulator::builder().seed(3).rng(RngKind::Pcg64).build().unwrap();
+    /// let played = simulator.play(&config, &Player::CLASSIC, 1000).unwrap();
+    /// let mut monty = MontyHall::seeded_with(RngKind::Pcg64, 3);
+    /// let traced = monty.play_multiple(&config, &Player::CLASSIC, 1000).unwrap();
+    /// assert_eq!(traced.result_sets(), played.result_sets());
+    /// ```
+    pub fn seeded_with(rng: RngKind, seed: u64) -> Self {
+        Self::new_with_rng(rng.seed_from_u64(rng::stream_seed(seed, 0)))
+            .with_rng_kind(rng)
+            .with_seed(seed)
+    }
 }
 
 impl Default for MontyHall<XorShiftRng> {
     fn default() -> Self {
-        Self::new_with_rng(XorShiftRng::seed_from_u64(0))
+        Self::new_with_rng(XorShiftRng::seed_from_u64(0)).with_rng_kind(RngKind::XorShift)
     }
 }
 
 /// A wrapper around [MontyHall::play_multiple] that splits the work by
 /// the amount of logical CPUs available.
 ///
+/// Equivalent to [play_threaded_seeded] with a seed of 0.
+///
 /// ```rust
-/// use monty_pyrs::{Results, play_threaded};
-/// let results: Results = play_threaded(1_000_000);
+/// use monty_pyrs::{GameConfig, Player, Results, play_threaded};
+/// let results: Results = play_threaded(&GameConfig::default(), &Player::CLASSIC, 1_000_000).unwrap();
 /// ```
-pub fn play_threaded(iterations: u64) -> Results {
-    let threads = num_cpus::get();
-
-    let iterations_per_thread = iterations / threads as u64;
-    let mut handles = Vec::with_capacity(threads);
-    for _ in 0..threads {
-        let iters = iterations_per_thread;
-        let mut monty = MontyHall::default();
-        handles.push(std::thread::spawn(move || monty.play_multiple(iters)));
-    }
-    let mut results = Results::default();
-    for handle in handles {
-        results += handle.join().unwrap();
-    }
-    results
-}
-
-/// Play one billion iterations of the Monty Hall simulation,
-/// returning a formatted string with the result.
-#[pyfunction]
-fn play_one_billion_times() -> PyResult<String> {
-    let iterations = 1_000_000_000;
-    let results = play_threaded(iterations);
-    let (switched_pct, stayed_pct) = results.calc_win_rate();
-    Ok(format!(
-        "Played {iterations} times, winning {switched_pct:.2}% of the time when switching and {stayed_pct:.2}% times when staying",
-        iterations = iterations,
-        switched_pct = switched_pct * 100.,
-        stayed_pct = stayed_pct * 100.
-    ))
-}
-
-#[pyfunction]
-/// Play a number of iterations of the Monty Hall simulation,
-/// returning the [Results]
-fn play(iterations: u64) -> PyResult<Results> {
-    Ok(play_threaded(iterations))
-}
-
-#[pymodule]
-fn monty_pyrs(_py: Python, m: &PyModule) -> PyResult<()> {
-    m.add_function(wrap_pyfunction!(play_one_billion_times, m)?)?;
-    m.add_function(wrap_pyfunction!(play, m)?)?;
-    m.add_class::<Results>()?;
-    Ok(())
+pub fn play_threaded(
+    config: &GameConfig,
+    players: &[Player],
+    iterations: u64,
+) -> Result<Results, MontyError> {
+    play_threaded_seeded(config, players, iterations, 0)
+}
+
+/// A wrapper around [MontyHall::play_multiple] that splits the work by
+/// the amount of logical CPUs available, seeding every random number
+/// generator from `seed`.
+///
+/// Runs on the default [Simulator], see there for how the work is split.
+/// Exactly `iterations` games are played, split between the strategies
+/// as in [MontyHall::play_multiple].
+///
+/// ```rust
+/// use monty_pyrs::{GameConfig, Player, play_threaded_seeded};
+///
+/// let config = GameConfig::default();
+/// let first = play_threaded_seeded(&config, &Player::CLASSIC, 1_000_000, 42).unwrap();
+/// let second = play_threaded_seeded(&config, &Player::CLASSIC, 1_000_000, 42).unwrap();
+/// let other = play_threaded_seeded(&config, &Player::CLASSIC, 1_000_000, 43).unwrap();
+/// assert_eq!(first, second);
+/// assert_ne!(first, other);
+///
+/// let players = [Player::AlwaysSwitch, Player::AlwaysStay, Player::RandomSwitch(0.5)];
+/// let results = play_threaded_seeded(&config, &players, 1_000_001, 42).unwrap();
+/// assert_eq!(results.total_games(), 1_000_001);
+/// assert_eq!(results.games("always_switch"), Some(333_334));
+/// assert_eq!(results.games("always_stay"), Some(333_334));
+/// assert_eq!(results.games("random_switch(0.5)"), Some(333_333));
+/// ```
+pub fn play_threaded_seeded(
+    config: &GameConfig,
+    players: &[Player],
+    iterations: u64,
+    seed: u64,
+) -> Result<Results, MontyError> {
+    Simulator::builder()
+        .seed(seed)
+        .build()?
+        .play(config, players, iterations)
+}
+
+/// Like [play_threaded], but calls `on_progress` every `interval` with the
+/// games played so far and the running win rates.
+///
+/// ```rust
+/// use monty_pyrs::{GameConfig, Player, play_threaded_with_progress};
+/// use std::time::Duration;
+///
+/// let results = play_threaded_with_progress(
+///     &GameConfig::default(),
+///     &Player::CLASSIC,
+///     1_000_000,
+///     Duration::from_secs(1),
+///     |progress| println!("{} games in {:?}: {:?}", progress.games_played(), progress.elapsed(), progress.win_rates()),
+/// ).unwrap();
+/// ```
+pub fn play_threaded_with_progress<F>(
+    config: &GameConfig,
+    players: &[Player],
+    iterations: u64,
+    interval: Duration,
+    on_progress: F,
+) -> Result<Results, MontyError>
+where
+    F: FnMut(&Progress),
+{
+    Simulator::builder().build()?.play_with_progress(
+        config,
+        players,
+        iterations,
+        &CancellationToken::new(),
+        interval,
+        on_progress,
+    )
 }
@@ -0,0 +1,119 @@
+//! Describes how [Results](crate::Results) were played, so that results of
+//! separate runs can be checked for compatibility before merging them.
+
+use crate::{Fidelity, GameConfig, HostStrategy, RngKind};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The game and settings of a single run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunMetadata {
+    version: String,
+    doors: u32,
+    opened_by_host: u32,
+    host: String,
+    fidelity: Fidelity,
+    seed: Option<u64>,
+    #[serde(default)]
+    rng: Option<RngKind>,
+    threads: usize,
+    #[serde(default)]
+    aggregate: bool,
+}
+
+impl RunMetadata {
+    pub(crate) fn new(
+        config: &GameConfig,
+        fidelity: Fidelity,
+        seed: Option<u64>,
+        rng: Option<RngKind>,
+        threads: usize,
+    ) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            doors: config.doors(),
+            opened_by_host: config.opened_by_host(),
+            host: config.host().name().to_string(),
+            fidelity,
+            seed,
+            rng,
+            threads,
+            aggregate: false,
+        }
+    }
+
+    /// Mark the run as sampled from the exact distribution rather than played.
+    pub(crate) fn aggregate(self) -> Self {
+        Self {
+            aggregate: true,
+            ..self
+        }
+    }
+
+    /// The version of this crate that played the games.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// The total number of doors.
+    pub fn doors(&self) -> u32 {
+        self.doors
+    }
+
+    /// The number of doors the host opened.
+    pub fn opened_by_host(&self) -> u32 {
+        self.opened_by_host
+    }
+
+    /// The name of the host model.
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// How faithfully the games were simulated.
+    pub fn fidelity(&self) -> Fidelity {
+        self.fidelity
+    }
+
+    /// The seed of the run, or `None` if the random number generator was
+    /// provided by the caller.
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// The random number generator the games were played with, or `None` if
+    /// it was provided by the caller.
+    pub fn rng(&self) -> Option<RngKind> {
+        self.rng
+    }
+
+    /// The number of worker threads the run was split across.
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Whether the counts were sampled from their exact distribution rather
+    /// than counted from played games, see [MontyHall::play_aggregate](crate::MontyHall::play_aggregate).
+    pub fn is_aggregate(&self) -> bool {
+        self.aggregate
+    }
+
+    /// Whether both runs played the same game, so that their results can be added up.
+    pub(crate) fn same_game(&self, other: &Self) -> bool {
+        self.doors == other.doors
+            && self.opened_by_host == other.opened_by_host
+            && self.host == other.host
+            && self.fidelity == other.fidelity
+    }
+}
+
+/// Describes the game, eg. `3 doors, 1 opened by the knowledgeable host (fast)`.
+impl fmt::Display for RunMetadata {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} doors, {} opened by the {} host ({})",
+            self.doors, self.opened_by_host, self.host, self.fidelity
+        )
+    }
+}
@@ -0,0 +1,185 @@
+//! Strategies the player can follow: which door to pick first and whether to
+//! switch once the host has opened doors.
+
+use crate::MontyError;
+use rand_core::RngCore;
+use std::{fmt, str::FromStr, sync::Arc};
+
+/// What the player gets to see before deciding whether to switch.
+pub struct Reveal<'a> {
+    pub(crate) choice: u32,
+    pub(crate) opened: &'a [u32],
+    pub(crate) closed: &'a [u32],
+}
+
+impl Reveal<'_> {
+    /// The door chosen initially.
+    pub fn choice(&self) -> u32 {
+        self.choice
+    }
+
+    /// The doors opened by the host, in the order they were opened.
+    pub fn opened(&self) -> &[u32] {
+        self.opened
+    }
+
+    /// The doors the player can switch to, in ascending order.
+    pub fn closed(&self) -> &[u32] {
+        self.closed
+    }
+}
+
+/// Decides the initial pick and whether to switch after the reveal.
+///
+/// Implement this to simulate a strategy not covered by [Player] and pass it
+/// in using [Player::Custom].
+pub trait PlayerStrategy: Send + Sync {
+    /// The door to pick initially. Returning `None` leaves the choice to the
+    /// simulator. Picks must be lower than `doors`.
+    fn initial_pick(&self, _rng: &mut dyn RngCore, _doors: u32) -> Option<u32> {
+        None
+    }
+
+    /// Decide whether to switch to one of the closed doors.
+    fn switch(&self, rng: &mut dyn RngCore, reveal: &Reveal) -> bool;
+
+    /// A name identifying the strategy. Results are tracked per name.
+    fn name(&self) -> String;
+}
+
+/// The built-in player strategies.
+///
+/// ```rust
+/// use monty_pyrs::{GameConfig, MontyHall, Player};
+/// use assert_approx_eq::assert_approx_eq;
+///
+/// let mut monty = MontyHall::default();
+/// let players = [Player::AlwaysSwitch, Player::RandomSwitch(0.5)];
+/// let results = monty.play_multiple(&GameConfig::default(), &players, 1_000_000).unwrap();
+/// // Switching half the time wins half the time
+/// assert_approx_eq!(results.win_rate("random_switch(0.5)").unwrap(), 0.5, 0.005);
+/// ```
+#[derive(Clone)]
+pub enum Player {
+    /// Always switches when offered the chance.
+    AlwaysSwitch,
+    /// Never switches.
+    AlwaysStay,
+    /// Switches with the given probability.
+    RandomSwitch(f64),
+    /// Switches only if the host opened the given door.
+    SwitchIfHostOpened(u32),
+    /// A user-provided strategy.
+    Custom(Arc<dyn PlayerStrategy>),
+}
+
+impl Player {
+    /// The two strategies of the classic problem: switching and staying.
+    pub const CLASSIC: [Player; 2] = [Player::AlwaysSwitch, Player::AlwaysStay];
+
+    /// Check that the strategy can be played, ie. that the probability of
+    /// [Player::RandomSwitch] is between 0 and 1.
+    ///
+    /// ```rust
+    /// use monty_pyrs::{GameConfig, MontyHall, Player};
+    ///
+    /// assert!(Player::RandomSwitch(0.5).validate().is_ok());
+    /// assert!(Player::RandomSwitch(1.5).validate().is_err());
+    /// assert!(Player::RandomSwitch(f64::NAN).validate().is_err());
+    /// let players = [Player::RandomSwitch(-0.5)];
+    /// assert!(MontyHall::default().play_multiple(&GameConfig::default(), &players, 10).is_err());
+    /// ```
+    pub fn validate(&self) -> Result<(), MontyError> {
+        match self {
+            Player::RandomSwitch(probability) if !(0. ..=1.).contains(probability) => Err(
+                MontyError::InvalidSwitchProbability(probability.to_string()),
+            ),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl PlayerStrategy for Player {
+    fn initial_pick(&self, rng: &mut dyn RngCore, doors: u32) -> Option<u32> {
+        match self {
+            Player::Custom(player) => player.initial_pick(rng, doors),
+            _ => None,
+        }
+    }
+
+    fn switch(&self, rng: &mut dyn RngCore, reveal: &Reveal) -> bool {
+        match self {
+            Player::AlwaysSwitch => true,
+            Player::AlwaysStay => false,
+            Player::RandomSwitch(probability) => {
+                // Use the top 53 bits for a uniformly distributed float in [0, 1)
+                let sample = (rng.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+                sample < *probability
+            }
+            Player::SwitchIfHostOpened(door) => reveal.opened().contains(door),
+            Player::Custom(player) => player.switch(rng, reveal),
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Player::AlwaysSwitch => "always_switch".to_string(),
+            Player::AlwaysStay => "always_stay".to_string(),
+            Player::RandomSwitch(probability) => format!("random_switch({})", probability),
+            Player::SwitchIfHostOpened(door) => format!("switch_if_opened({})", door),
+            Player::Custom(player) => player.name(),
+        }
+    }
+}
+
+/// Players are compared by name, so custom players with the same name are considered equal.
+impl PartialEq for Player {
+    fn eq(&self, other: &Self) -> bool {
+        self.name() == other.name()
+    }
+}
+
+impl fmt::Debug for Player {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.name())
+    }
+}
+
+/// Parses the names of the built-in strategies, as returned by [PlayerStrategy::name].
+///
+/// ```rust
+/// use monty_pyrs::Player;
+///
+/// assert_eq!("random_switch(0.25)".parse(), Ok(Player::RandomSwitch(0.25)));
+/// assert_eq!("switch_if_opened(2)".parse(), Ok(Player::SwitchIfHostOpened(2)));
+/// assert!("random_switch(1.5)".parse::<Player>().is_err());
+/// ```
+impl FromStr for Player {
+    type Err = MontyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let unknown = || MontyError::UnknownPlayer(s.to_string());
+        match s {
+            "always_switch" => return Ok(Player::AlwaysSwitch),
+            "always_stay" => return Ok(Player::AlwaysStay),
+            _ => {}
+        }
+        // The remaining strategies take a single argument in parentheses
+        let (name, argument) = s
+            .strip_suffix(')')
+            .and_then(|s| s.split_once('('))
+            .ok_or_else(unknown)?;
+        match name {
+            "random_switch" => {
+                let player = Player::RandomSwitch(argument.parse().map_err(|_| unknown())?);
+                player.validate()?;
+                Ok(player)
+            }
+            "switch_if_opened" => argument
+                .parse()
+                .map(Player::SwitchIfHostOpened)
+                .map_err(|_| unknown()),
+            _ => Err(unknown()),
+        }
+    }
+}
@@ -0,0 +1,836 @@
+//! The Python module, built by Maturin as `monty_pyrs`.
+//!
+//! Simulations release the GIL while they run, so other Python threads
+//! keep running in the meantime. They can be interrupted with Ctrl-C.
+
+use crate::{
+    check_iterations, exact_win_rate, replay, trace::GameTrace, CancellationToken, GameConfig,
+    GameRecord, MontyError, MontyHall, Player, PlayerStrategy, Progress, ResultSet, Results,
+    Simulator, Sweep, SweepResults, TraceFormat, TraceWriter, CANCELLATION_CHECK_INTERVAL,
+};
+use num_rational::BigRational;
+use numpy::{PyArray2, PyArrayDyn};
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{IntoPyDict, PyCFunction, PyDict, PyTuple};
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::BufWriter;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+
+/// How often the calling thread checks for signals such as Ctrl-C while
+/// a simulation is running.
+const SIGNAL_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Play one billion iterations of the Monty Hall simulation,
+/// returning a formatted string with the result.
+#[pyfunction]
+fn play_one_billion_times(py: Python) -> PyResult<String> {
+    let iterations = 1_000_000_000;
+    let run = Run::new(iterations, 3, 1, "knowledgeable", None, 0, None, "xorshift")?;
+    let results = run.play_interruptible(py, OnInterrupt::Raise, None)?;
+    let (switched_pct, stayed_pct) = results.calc_win_rate()?;
+    Ok(format!(
+        "Played {iterations} times, winning {switched_pct:.2}% of the time when switching and {stayed_pct:.2}% times when staying",
+        iterations = iterations,
+        switched_pct = switched_pct * 100.,
+        stayed_pct = stayed_pct * 100.
+    ))
+}
+
+/// The arguments shared by [play] and [play_async], validated and ready to run.
+struct Run {
+    simulator: Simulator,
+    config: GameConfig,
+    players: Vec<Player>,
+    iterations: u64,
+}
+
+impl Run {
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        iterations: u64,
+        doors: u32,
+        opened_by_host: u32,
+        host: &str,
+        strategies: Option<Vec<String>>,
+        seed: u64,
+        threads: Option<usize>,
+        rng: &str,
+    ) -> PyResult<Self> {
+        let config = GameConfig::new(doors, opened_by_host)?.with_host(host.parse()?);
+        let players = parse_players(strategies)?;
+        check_iterations(&players, iterations)?;
+        Ok(Self {
+            simulator: build_simulator(seed, threads, rng)?,
+            config,
+            players,
+            iterations,
+        })
+    }
+
+    fn play(&self, token: &CancellationToken) -> Result<Results, MontyError> {
+        self.simulator
+            .play_cancellable(&self.config, &self.players, self.iterations, token)
+    }
+
+    /// Play with the GIL released, checking for signals from the calling thread
+    /// in the meantime and stopping the simulation if a handler raises.
+    /// Progress is reported to `reporter` if given, and exceptions raised by
+    /// it stop the simulation too.
+    fn play_interruptible(
+        &self,
+        py: Python,
+        on_interrupt: OnInterrupt,
+        reporter: Option<&ProgressReporter>,
+    ) -> PyResult<Results> {
+        let token = CancellationToken::new();
+        let mut interrupt = None;
+        let mut failure = None;
+        let mut next_report = reporter.map_or(Duration::ZERO, |reporter| reporter.interval);
+        let results = py.allow_threads(|| {
+            self.simulator.play_with_progress(
+                &self.config,
+                &self.players,
+                self.iterations,
+                &token,
+                SIGNAL_CHECK_INTERVAL,
+                |progress| {
+                    if interrupt.is_some() || failure.is_some() {
+                        return;
+                    }
+                    Python::with_gil(|py| {
+                        if let Err(err) = py.check_signals() {
+                            token.cancel();
+                            interrupt = Some(err);
+                            return;
+                        }
+                        let reporter = match reporter {
+                            Some(reporter) => reporter,
+                            None => return,
+                        };
+                        if progress.is_finished() || progress.elapsed() >= next_report {
+                            next_report = progress.elapsed() + reporter.interval;
+                            if let Err(err) = reporter.report(py, progress) {
+                                token.cancel();
+                                failure = Some(err);
+                            }
+                        }
+                    });
+                },
+            )
+        });
+        if let Some(err) = failure {
+            return Err(err);
+        }
+        match (interrupt, on_interrupt) {
+            (Some(err), OnInterrupt::Raise) => Err(err),
+            _ => Ok(results?),
+        }
+    }
+}
+
+/// A Python callable to report progress to, and how often to call it.
+struct ProgressReporter {
+    callback: PyObject,
+    interval: Duration,
+}
+
+impl ProgressReporter {
+    fn new(callback: PyObject, interval: f64) -> PyResult<Self> {
+        match Duration::try_from_secs_f64(interval) {
+            Ok(interval) if !interval.is_zero() => Ok(Self { callback, interval }),
+            _ => Err(PyValueError::new_err(format!(
+                "progress_interval must be a positive number of seconds, not {}",
+                interval
+            ))),
+        }
+    }
+
+    /// Call the callable with the games played, the seconds elapsed and a dict
+    /// of running win rates.
+    fn report(&self, py: Python, progress: &Progress) -> PyResult<()> {
+        let win_rates = progress.win_rates().into_py_dict(py);
+        self.callback.call1(
+            py,
+            (
+                progress.games_played(),
+                progress.elapsed().as_secs_f64(),
+                win_rates,
+            ),
+        )?;
+        Ok(())
+    }
+}
+
+/// What [play] does when interrupted, eg. by a `KeyboardInterrupt`.
+enum OnInterrupt {
+    /// Raise the exception.
+    Raise,
+    /// Return the results played so far, marked as incomplete.
+    Partial,
+}
+
+impl OnInterrupt {
+    fn parse(value: &str) -> PyResult<Self> {
+        match value {
+            "raise" => Ok(OnInterrupt::Raise),
+            "partial" => Ok(OnInterrupt::Partial),
+            _ => Err(PyValueError::new_err(format!(
+                "on_interrupt must be \"raise\" or \"partial\", not {:?}",
+                value
+            ))),
+        }
+    }
+}
+
+#[pyfunction(
+    doors = "3",
+    opened_by_host = "1",
+    host = "\"knowledgeable\"",
+    strategies = "None",
+    seed = "0",
+    threads = "None",
+    rng = "\"xorshift\"",
+    aggregate = "false",
+    on_interrupt = "\"raise\"",
+    progress = "None",
+    progress_interval = "1.0"
+)]
+/// Play a number of iterations of the Monty Hall simulation,
+/// returning the [Results]
+///
+/// `host` is the name of one of the built-in [Host](crate::Host) models and
+/// `strategies` a list of names of built-in [Player] strategies, defaulting to
+/// always switching and always staying. Runs with the same `seed` and `rng`
+/// give the same results. `threads` defaults to the number of logical CPUs.
+///
+/// `rng` names the random number generator: `"xorshift"`, the fastest,
+/// `"pcg64"`, `"chacha8"`, `"chacha20"`, `"wyrand"` or `"splitmix"`. With
+/// `"xorshift"`, always switching and always staying are played with
+/// vectorized xoshiro256** generators seeded from it; every other generator
+/// plays all games itself.
+///
+/// With `aggregate=True` the games aren't played: the counts are sampled from
+/// their exact distribution in constant time, see
+/// [Simulator::play_aggregate], and the results are marked as aggregate.
+/// Only strategies with a known exact win rate can be sampled.
+///
+/// When interrupted, eg. with Ctrl-C, the simulation stops and the exception
+/// is raised, or with `on_interrupt="partial"` the results played so far are
+/// returned, marked as incomplete.
+///
+/// If `progress` is given, it is called every `progress_interval` seconds and
+/// once more at the end, with the number of games played so far, the seconds
+/// elapsed and a dict of the running win rates per strategy.
+///
+/// Raises `ValueError` if the host cannot open `opened_by_host` doors
+/// or a host, strategy or generator name is unknown, `threads` is 0, there
+/// are fewer `iterations` than strategies or a strategy can't be sampled.
+#[allow(clippy::too_many_arguments)]
+fn play(
+    py: Python,
+    iterations: u64,
+    doors: u32,
+    opened_by_host: u32,
+    host: &str,
+    strategies: Option<Vec<String>>,
+    seed: u64,
+    threads: Option<usize>,
+    rng: &str,
+    aggregate: bool,
+    on_interrupt: &str,
+    progress: Option<PyObject>,
+    progress_interval: f64,
+) -> PyResult<Results> {
+    let on_interrupt = OnInterrupt::parse(on_interrupt)?;
+    let reporter = progress
+        .map(|callback| ProgressReporter::new(callback, progress_interval))
+        .transpose()?;
+    let run = Run::new(
+        iterations,
+        doors,
+        opened_by_host,
+        host,
+        strategies,
+        seed,
+        threads,
+        rng,
+    )?;
+    if aggregate {
+        return Ok(run
+            .simulator
+            .play_aggregate(&run.config, &run.players, run.iterations)?);
+    }
+    run.play_interruptible(py, on_interrupt, reporter.as_ref())
+}
+
+#[pyfunction(
+    doors = "3",
+    opened_by_host = "1",
+    host = "\"knowledgeable\"",
+    strategies = "None",
+    seed = "0",
+    threads = "None",
+    rng = "\"xorshift\""
+)]
+/// Like [play], but returns an `asyncio.Future` resolving to the [Results]
+/// instead of blocking. Cancelling the future stops the simulation.
+/// Must be called from a running event loop:
+///
+/// ```python
+/// results = await monty_pyrs.play_async(1_000_000_000)
+/// ```
+#[allow(clippy::too_many_arguments)]
+fn play_async(
+    py: Python,
+    iterations: u64,
+    doors: u32,
+    opened_by_host: u32,
+    host: &str,
+    strategies: Option<Vec<String>>,
+    seed: u64,
+    threads: Option<usize>,
+    rng: &str,
+) -> PyResult<PyObject> {
+    let run = Run::new(
+        iterations,
+        doors,
+        opened_by_host,
+        host,
+        strategies,
+        seed,
+        threads,
+        rng,
+    )?;
+    let event_loop = py.import("asyncio")?.call_method0("get_running_loop")?;
+    let future = event_loop.call_method0("create_future")?;
+    let token = CancellationToken::new();
+    let canceller = token.clone();
+    let cancel = move |args: &PyTuple, _kwargs: Option<&PyDict>| -> PyResult<()> {
+        if args.get_item(0)?.call_method0("cancelled")?.is_true()? {
+            canceller.cancel();
+        }
+        Ok(())
+    };
+    future.call_method1(
+        "add_done_callback",
+        (PyCFunction::new_closure(cancel, py)?,),
+    )?;
+    let (event_loop, awaitable): (PyObject, PyObject) = (event_loop.into(), future.into());
+    let future = awaitable.clone_ref(py);
+
+    std::thread::spawn(move || {
+        let outcome = match panic::catch_unwind(AssertUnwindSafe(|| run.play(&token))) {
+            Ok(results) => results.map_err(PyErr::from),
+            Err(_) => Err(PyRuntimeError::new_err("the simulation panicked")),
+        };
+        // Cancelled with the awaiting task, so nobody is waiting for the outcome
+        if token.is_cancelled() {
+            return;
+        }
+        Python::with_gil(|py| {
+            // The future can only be completed from the thread running the event loop
+            let complete = move |args: &PyTuple, _kwargs: Option<&PyDict>| -> PyResult<()> {
+                let py = args.py();
+                let future = future.as_ref(py);
+                // Nothing to do if the awaiting task was cancelled in the meantime
+                if future.call_method0("done")?.is_true()? {
+                    return Ok(());
+                }
+                match &outcome {
+                    Ok(results) => {
+                        future.call_method1("set_result", (Py::new(py, results.clone())?,))?
+                    }
+                    Err(err) => future.call_method1("set_exception", (err.clone_ref(py),))?,
+                };
+                Ok(())
+            };
+            let result = PyCFunction::new_closure(complete, py).and_then(|complete| {
+                event_loop.call_method1(py, "call_soon_threadsafe", (complete,))
+            });
+            // The event loop raises `RuntimeError` if it was closed while the
+            // simulation was running, leaving nobody to notify
+            if let Err(err) = result {
+                if !err.is_instance::<PyRuntimeError>(py) {
+                    err.print(py);
+                }
+            }
+        });
+    });
+
+    Ok(awaitable)
+}
+
+#[pyfunction(
+    confidence = "0.95",
+    max_iterations = "None",
+    doors = "3",
+    opened_by_host = "1",
+    host = "\"knowledgeable\"",
+    strategies = "None",
+    seed = "0",
+    threads = "None",
+    rng = "\"xorshift\""
+)]
+/// Play until the win rate of every strategy is known to within `precision`
+/// either way at the `confidence` level, as [Simulator::play_until], rather
+/// than a fixed number of games. Returns the [Results] and the number of
+/// games played. `max_iterations` caps the games played, by default at
+/// the largest 64-bit count.
+///
+/// ```python
+/// results, games = monty_pyrs.play_until(0.0001)
+/// ```
+///
+/// Raises `ValueError` for the same reasons as [play], and if `precision` or
+/// `confidence` is not strictly between 0 and 1. Interrupting the run, eg.
+/// with Ctrl-C, raises the exception.
+#[allow(clippy::too_many_arguments)]
+fn play_until(
+    py: Python,
+    precision: f64,
+    confidence: f64,
+    max_iterations: Option<u64>,
+    doors: u32,
+    opened_by_host: u32,
+    host: &str,
+    strategies: Option<Vec<String>>,
+    seed: u64,
+    threads: Option<usize>,
+    rng: &str,
+) -> PyResult<(Results, u64)> {
+    let config = GameConfig::new(doors, opened_by_host)?.with_host(host.parse()?);
+    let players = parse_players(strategies)?;
+    let simulator = build_simulator(seed, threads, rng)?;
+    let results = run_interruptible(py, |token| {
+        simulator.play_until_cancellable(
+            &config,
+            &players,
+            precision,
+            confidence,
+            max_iterations.unwrap_or(u64::MAX),
+            token,
+        )
+    })?;
+    Ok(results?)
+}
+
+#[pyfunction(
+    doors = "None",
+    opened_by_host = "None",
+    switch_probabilities = "None",
+    host = "\"knowledgeable\"",
+    seed = "0",
+    threads = "None",
+    rng = "\"xorshift\""
+)]
+/// Play `iterations` games of every combination of `doors`, `opened_by_host`
+/// and `switch_probabilities`, returning the [SweepResults]. Each parameter
+/// takes a list or a `range`, defaulting to the classic game played always
+/// switching and never switching. Combinations where the host can't open
+/// that many doors are skipped.
+///
+/// ```python
+/// table = monty_pyrs.sweep(1_000_000, doors=range(3, 11), switch_probabilities=[0, 0.5, 1])
+/// table.to_pandas().pivot(index="doors", columns="switch_probability", values="win_rate")
+/// ```
+///
+/// Raises `ValueError` if no combination is a valid game, a switch probability
+/// is not between 0 and 1, the host name is unknown, `threads` is 0 or
+/// `iterations` is 0. Interrupting the sweep, eg. with Ctrl-C, raises the exception.
+#[allow(clippy::too_many_arguments)]
+fn sweep(
+    py: Python,
+    iterations: u64,
+    doors: Option<Vec<u32>>,
+    opened_by_host: Option<Vec<u32>>,
+    switch_probabilities: Option<Vec<f64>>,
+    host: &str,
+    seed: u64,
+    threads: Option<usize>,
+    rng: &str,
+) -> PyResult<SweepResults> {
+    let mut sweep = Sweep::new().host(host.parse()?);
+    if let Some(doors) = doors {
+        sweep = sweep.doors(doors);
+    }
+    if let Some(opened_by_host) = opened_by_host {
+        sweep = sweep.opened_by_host(opened_by_host);
+    }
+    if let Some(switch_probabilities) = switch_probabilities {
+        sweep = sweep.switch_probabilities(switch_probabilities);
+    }
+    let simulator = build_simulator(seed, threads, rng)?;
+
+    let results = run_interruptible(py, |token| {
+        simulator.sweep_cancellable(&sweep, iterations, token)
+    })?;
+    Ok(results?)
+}
+
+/// Run `play` on another thread with the GIL released, checking for signals
+/// from the calling thread in the meantime. If a handler raises, the token
+/// passed to `play` is cancelled and the exception raised once it returns.
+fn run_interruptible<T, F>(py: Python, play: F) -> PyResult<T>
+where
+    T: Send,
+    F: FnOnce(&CancellationToken) -> T + Send,
+{
+    let token = CancellationToken::new();
+    let mut interrupt = None;
+    let result = py.allow_threads(|| {
+        std::thread::scope(|scope| {
+            let (finished, is_finished) = mpsc::channel();
+            let token = &token;
+            let worker = scope.spawn(move || {
+                let result = play(token);
+                let _ = finished.send(());
+                result
+            });
+            while let Err(RecvTimeoutError::Timeout) =
+                is_finished.recv_timeout(SIGNAL_CHECK_INTERVAL)
+            {
+                if interrupt.is_none() {
+                    if let Err(err) = Python::with_gil(|py| py.check_signals()) {
+                        token.cancel();
+                        interrupt = Some(err);
+                    }
+                }
+            }
+            worker
+                .join()
+                .unwrap_or_else(|payload| panic::resume_unwind(payload))
+        })
+    });
+    match interrupt {
+        Some(err) => Err(err),
+        None => Ok(result),
+    }
+}
+
+/// Convert an array-like of non-negative integers, or a single one, to a vector
+/// in one go rather than element by element.
+fn to_counts(py: Python, values: &PyAny, name: &str) -> PyResult<Vec<u64>> {
+    let array = py.import("numpy")?.call_method1("atleast_1d", (values,))?;
+    // Integers of any width convert, floats and other kinds raise `TypeError`
+    let kwargs = [("casting", "same_kind")].into_py_dict(py);
+    let array: &PyArrayDyn<i64> = array
+        .call_method("astype", ("int64",), Some(kwargs))?
+        .downcast()?;
+    if array.ndim() != 1 {
+        return Err(PyValueError::new_err(format!(
+            "{} must be a one-dimensional array, not {}-dimensional",
+            name,
+            array.ndim()
+        )));
+    }
+    array
+        .to_vec()?
+        .into_iter()
+        .map(|value| {
+            if value < 0 {
+                Err(PyValueError::new_err(format!(
+                    "{} must not be negative, not {}",
+                    name, value
+                )))
+            } else {
+                Ok(value as u64)
+            }
+        })
+        .collect()
+}
+
+/// Convert a count of doors to the door numbers [GameConfig] takes.
+fn to_door_count(value: u64, name: &str) -> PyResult<u32> {
+    u32::try_from(value).map_err(|_| {
+        PyValueError::new_err(format!(
+            "{} must be at most {}, not {}",
+            name,
+            u32::MAX,
+            value
+        ))
+    })
+}
+
+/// Repeat an array of length one to `len` elements, numpy style.
+fn broadcast<T: Copy>(values: Vec<T>, len: usize, name: &str) -> PyResult<Vec<T>> {
+    match values.len() {
+        n if n == len => Ok(values),
+        1 => Ok(vec![values[0]; len]),
+        n => Err(PyValueError::new_err(format!(
+            "{} has {} elements, which doesn't match the other arrays of {}",
+            name, n, len
+        ))),
+    }
+}
+
+#[pyfunction(
+    doors = "None",
+    opened_by_host = "None",
+    host = "\"knowledgeable\"",
+    strategies = "None",
+    seed = "0",
+    threads = "None",
+    rng = "\"xorshift\""
+)]
+/// Play many independent simulations in a single call, as
+/// [Simulator::play_batch]. `iterations`, `doors` and `opened_by_host` take
+/// NumPy arrays, or anything `numpy.asarray` accepts, with one element per
+/// simulation. Arrays of a single element and plain integers apply to every
+/// simulation. The game defaults to the classic one.
+///
+/// Returns a dict with the `"strategies"` played, in the order of `strategies`,
+/// and the `"wins"`, `"losses"` and `"voided"` counts as NumPy `uint64` arrays
+/// with one row per simulation and one column per strategy:
+///
+/// ```python
+/// batch = monty_pyrs.play_batch(np.full(10_000, 100))
+/// switch_rates = batch["wins"][:, 0] / (batch["wins"][:, 0] + batch["losses"][:, 0])
+/// ```
+///
+/// Raises `ValueError` for the same reasons as [play], for negative counts and
+/// for arrays whose lengths don't match, and `TypeError` for arrays of anything
+/// but integers.
+#[allow(clippy::too_many_arguments)]
+fn play_batch<'py>(
+    py: Python<'py>,
+    iterations: &PyAny,
+    doors: Option<PyObject>,
+    opened_by_host: Option<PyObject>,
+    host: &str,
+    strategies: Option<Vec<String>>,
+    seed: u64,
+    threads: Option<usize>,
+    rng: &str,
+) -> PyResult<&'py PyDict> {
+    let iterations = to_counts(py, iterations, "iterations")?;
+    let doors = match doors {
+        Some(doors) => to_counts(py, doors.as_ref(py), "doors")?,
+        None => vec![3],
+    };
+    let opened_by_host = match opened_by_host {
+        Some(opened_by_host) => to_counts(py, opened_by_host.as_ref(py), "opened_by_host")?,
+        None => vec![1],
+    };
+    let len = iterations.len().max(doors.len()).max(opened_by_host.len());
+    let iterations = broadcast(iterations, len, "iterations")?;
+    let doors = broadcast(doors, len, "doors")?;
+    let opened_by_host = broadcast(opened_by_host, len, "opened_by_host")?;
+
+    let host: crate::Host = host.parse()?;
+    let games = iterations
+        .iter()
+        .zip(doors.iter().zip(&opened_by_host))
+        .map(|(&iterations, (&doors, &opened_by_host))| {
+            let config = GameConfig::new(
+                to_door_count(doors, "doors")?,
+                to_door_count(opened_by_host, "opened_by_host")?,
+            )?;
+            Ok((config.with_host(host.clone()), iterations))
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    let players = parse_players(strategies)?;
+    let simulator = build_simulator(seed, threads, rng)?;
+    let batch = run_interruptible(py, |token| {
+        simulator.play_batch_cancellable(&games, &players, token)
+    })??;
+
+    let names: Vec<String> = players.iter().map(|player| player.name()).collect();
+    let column = |count: fn(&ResultSet) -> u64| -> PyResult<&'py PyArray2<u64>> {
+        let rows: Vec<Vec<u64>> = batch
+            .iter()
+            .map(|results| {
+                names
+                    .iter()
+                    .map(|name| results.result_set(name).map_or(0, |set| count(&set)))
+                    .collect()
+            })
+            .collect();
+        Ok(PyArray2::from_vec2(py, &rows)?)
+    };
+    let dict = PyDict::new(py);
+    dict.set_item("strategies", &names)?;
+    dict.set_item("wins", column(ResultSet::wins)?)?;
+    dict.set_item("losses", column(ResultSet::losses)?)?;
+    dict.set_item("voided", column(ResultSet::voided)?)?;
+    Ok(dict)
+}
+
+#[pyfunction(
+    doors = "3",
+    opened_by_host = "1",
+    host = "\"knowledgeable\"",
+    strategies = "None",
+    seed = "0",
+    fidelity = "\"faithful\"",
+    rng = "\"xorshift\""
+)]
+/// Play `iterations` games one at a time, returning an iterator over the
+/// [GameRecord] of each game, with the doors hiding the car, picked, opened
+/// and finally chosen:
+///
+/// ```python
+/// for record in monty_pyrs.trace(5):
+///     print(record)
+/// ```
+///
+/// The games are handed out to the strategies in turn, and are played as the
+/// iterator is advanced. Unlike [play], `fidelity` defaults to `"faithful"`,
+/// so that the initial pick and the doors opened are random rather than
+/// always the first ones. With the same `seed`, `fidelity` and `rng`, the
+/// games are the first ones [play] plays, unless [play] decides them in bulk,
+/// as it does when only always switching and always staying are played
+/// against a host that never reveals the car.
+///
+/// Raises `ValueError` for the same reasons as [play] and for a `fidelity`
+/// other than `"fast"` and `"faithful"`.
+#[allow(clippy::too_many_arguments)]
+fn trace(
+    iterations: u64,
+    doors: u32,
+    opened_by_host: u32,
+    host: &str,
+    strategies: Option<Vec<String>>,
+    seed: u64,
+    fidelity: &str,
+    rng: &str,
+) -> PyResult<GameTrace> {
+    let config = GameConfig::new(doors, opened_by_host)?.with_host(host.parse()?);
+    let players = parse_players(strategies)?;
+    check_iterations(&players, iterations)?;
+    let monty = MontyHall::seeded_with(rng.parse()?, seed).with_fidelity(fidelity.parse()?);
+    Ok(GameTrace::new(monty, config, players, iterations))
+}
+
+#[pyfunction(
+    format = "\"binary\"",
+    doors = "3",
+    opened_by_host = "1",
+    host = "\"knowledgeable\"",
+    strategies = "None",
+    seed = "0",
+    fidelity = "\"faithful\"",
+    rng = "\"xorshift\""
+)]
+/// Play `iterations` games like [trace], streaming their records to the file
+/// at `path` as `"csv"`, `"ndjson"` or `"binary"` rather than returning them.
+/// Returns the number of games written. Load the [Results] back with
+/// [replay_trace].
+///
+/// Raises `ValueError` for the same reasons as [trace], an unknown `format`
+/// and failures to write the file. Interrupting the trace, eg. with Ctrl-C,
+/// stops it and raises the exception, leaving a complete trace of the games
+/// played so far.
+#[allow(clippy::too_many_arguments)]
+fn write_trace(
+    py: Python,
+    path: &str,
+    iterations: u64,
+    format: &str,
+    doors: u32,
+    opened_by_host: u32,
+    host: &str,
+    strategies: Option<Vec<String>>,
+    seed: u64,
+    fidelity: &str,
+    rng: &str,
+) -> PyResult<u64> {
+    let format: TraceFormat = format.parse()?;
+    let config = GameConfig::new(doors, opened_by_host)?.with_host(host.parse()?);
+    let players = parse_players(strategies)?;
+    let mut monty = MontyHall::seeded_with(rng.parse()?, seed).with_fidelity(fidelity.parse()?);
+    // Checked before creating the file so that invalid runs leave nothing behind
+    check_iterations(&players, iterations)?;
+    let file = File::create(path).map_err(|err| MontyError::Trace(err.to_string()))?;
+    let written = run_interruptible(py, |token| -> Result<u64, MontyError> {
+        let mut writer = TraceWriter::new(BufWriter::new(file), format, &players)?;
+        for (game, record) in monty.trace(&config, &players, iterations)?.enumerate() {
+            if (game as u64).is_multiple_of(CANCELLATION_CHECK_INTERVAL) && token.is_cancelled() {
+                break;
+            }
+            writer.write(&record)?;
+        }
+        let written = writer.games();
+        writer.finish()?;
+        Ok(written)
+    })?;
+    Ok(written?)
+}
+
+#[pyfunction]
+/// Read a trace written by [write_trace] in any of its formats and add up the
+/// games into [Results], to check the trace against the results of a run.
+///
+/// Raises `ValueError` if the file can't be read or is not a complete trace.
+fn replay_trace(py: Python, path: &str) -> PyResult<Results> {
+    let file = File::open(path).map_err(|err| MontyError::Trace(err.to_string()))?;
+    Ok(py.allow_threads(|| replay(file))?)
+}
+
+/// Build a simulator with the generator named `rng`, on `threads` threads
+/// defaulting to the number of logical CPUs.
+fn build_simulator(seed: u64, threads: Option<usize>, rng: &str) -> Result<Simulator, MontyError> {
+    let mut builder = Simulator::builder().seed(seed).rng(rng.parse()?);
+    if let Some(threads) = threads {
+        builder = builder.threads(threads);
+    }
+    builder.build()
+}
+
+/// Parse the names of built-in strategies, defaulting to always switching and
+/// always staying.
+fn parse_players(strategies: Option<Vec<String>>) -> Result<Vec<Player>, MontyError> {
+    match strategies {
+        Some(names) => names.iter().map(|name| name.parse()).collect(),
+        None => Ok(Player::CLASSIC.to_vec()),
+    }
+}
+
+/// Convert an exact probability to a `fractions.Fraction`.
+pub(crate) fn to_fraction(py: Python, ratio: &BigRational) -> PyResult<PyObject> {
+    let fraction = py.import("fractions")?.getattr("Fraction")?;
+    Ok(fraction.call1((ratio.to_string(),))?.into())
+}
+
+#[pyfunction(doors = "3", opened_by_host = "1", host = "\"knowledgeable\"")]
+/// The exact probability of the named strategy winning, as a `fractions.Fraction`,
+/// or `None` if it can't be calculated. Like the simulated win rates, the
+/// probability is conditioned on the game not being voided.
+///
+/// Raises `ValueError` if the host cannot open `opened_by_host` doors
+/// or the host or strategy name is unknown.
+#[pyo3(name = "exact_win_rate")]
+fn py_exact_win_rate(
+    py: Python,
+    strategy: &str,
+    doors: u32,
+    opened_by_host: u32,
+    host: &str,
+) -> PyResult<Option<PyObject>> {
+    let config = GameConfig::new(doors, opened_by_host)?.with_host(host.parse()?);
+    exact_win_rate(&config, &strategy.parse()?)
+        .map(|rate| to_fraction(py, &rate))
+        .transpose()
+}
+
+#[pymodule]
+fn monty_pyrs(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(play_one_billion_times, m)?)?;
+    m.add_function(wrap_pyfunction!(play, m)?)?;
+    m.add_function(wrap_pyfunction!(play_async, m)?)?;
+    m.add_function(wrap_pyfunction!(play_until, m)?)?;
+    m.add_function(wrap_pyfunction!(py_exact_win_rate, m)?)?;
+    m.add_function(wrap_pyfunction!(sweep, m)?)?;
+    m.add_function(wrap_pyfunction!(play_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(trace, m)?)?;
+    m.add_function(wrap_pyfunction!(write_trace, m)?)?;
+    m.add_function(wrap_pyfunction!(replay_trace, m)?)?;
+    m.add_class::<Results>()?;
+    m.add_class::<ResultSet>()?;
+    m.add_class::<SweepResults>()?;
+    m.add_class::<GameRecord>()?;
+    Ok(())
+}
@@ -0,0 +1,263 @@
+//! The random number generators simulations can be played with, helpers for
+//! seeding them and for drawing doors from them without bias.
+
+use crate::MontyError;
+use derive_more::Display;
+use rand_chacha::{ChaCha20Rng, ChaCha8Rng};
+use rand_core::{impls, Error, RngCore, SeedableRng};
+use rand_pcg::Pcg64;
+use rand_xorshift::XorShiftRng;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// One step of the [SplitMix64](https://prng.di.unimi.it/splitmix64.c) generator.
+///
+/// Consecutive inputs produce well-distributed, uncorrelated outputs, which makes
+/// it a good way of turning a single seed into seeds for many independent streams.
+pub(crate) fn splitmix64(state: u64) -> u64 {
+    let mut z = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Derive the seed of the numbered `stream` from the seed of the whole run.
+pub(crate) fn stream_seed(seed: u64, stream: u64) -> u64 {
+    splitmix64(splitmix64(seed) ^ stream.wrapping_mul(0x9e37_79b9_7f4a_7c15))
+}
+
+/// Draw a number below `n` with every number equally likely, using
+/// [Lemire's method](https://arxiv.org/abs/1805.10941).
+///
+/// Taking `next_u32() % n` favours the low numbers whenever `n` is not a
+/// power of two, by up to `n` in 2^32. Here the rare draws that would do so
+/// are rejected instead, so custom hosts and players should use this to pick doors.
+///
+/// ```rust
+/// use monty_pyrs::uniform_below;
+/// use rand_core::SeedableRng;
+/// use rand_xorshift::XorShiftRng;
+///
+/// let mut rng = XorShiftRng::seed_from_u64(0);
+/// let mut counts = [0; 3];
+/// for _ in 0..30_000 {
+///     counts[uniform_below(&mut rng, 3) as usize] += 1;
+/// }
+/// assert!(counts.iter().all(|&count| (9_500..10_500).contains(&count)));
+/// ```
+///
+/// Panics if `n` is 0.
+#[inline]
+pub fn uniform_below<R: RngCore + ?Sized>(rng: &mut R, n: u32) -> u32 {
+    assert!(n > 0, "can't draw a number below 0");
+    let mut product = u64::from(rng.next_u32()) * u64::from(n);
+    if (product as u32) < n {
+        // 2^32 % n, the number of low products that would bias the draw
+        let threshold = n.wrapping_neg() % n;
+        while (product as u32) < threshold {
+            product = u64::from(rng.next_u32()) * u64::from(n);
+        }
+    }
+    (product >> 32) as u32
+}
+
+/// The random number generators the [Simulator](crate::Simulator) and the
+/// Python API can play with, named as in Python.
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum RngKind {
+    /// [XorShiftRng], the fastest, but fails some statistical tests.
+    #[default]
+    #[display(fmt = "xorshift")]
+    #[serde(rename = "xorshift")]
+    XorShift,
+    /// The 128-bit PCG XSL RR 64 generator.
+    #[display(fmt = "pcg64")]
+    #[serde(rename = "pcg64")]
+    Pcg64,
+    /// ChaCha with 8 rounds, cryptographically strong enough for simulations.
+    #[display(fmt = "chacha8")]
+    #[serde(rename = "chacha8")]
+    ChaCha8,
+    /// ChaCha with the full 20 rounds.
+    #[display(fmt = "chacha20")]
+    #[serde(rename = "chacha20")]
+    ChaCha20,
+    /// [WyRand], nearly as fast as xorshift and passes the statistical tests.
+    #[display(fmt = "wyrand")]
+    #[serde(rename = "wyrand")]
+    WyRand,
+    /// [SplitMix64], also used to seed the other generators.
+    #[display(fmt = "splitmix")]
+    #[serde(rename = "splitmix")]
+    SplitMix,
+}
+
+impl RngKind {
+    /// Every kind of generator.
+    pub const ALL: [RngKind; 6] = [
+        RngKind::XorShift,
+        RngKind::Pcg64,
+        RngKind::ChaCha8,
+        RngKind::ChaCha20,
+        RngKind::WyRand,
+        RngKind::SplitMix,
+    ];
+
+    /// A generator of this kind, seeded with `seed`.
+    pub fn seed_from_u64(self, seed: u64) -> AnyRng {
+        match self {
+            RngKind::XorShift => AnyRng::XorShift(XorShiftRng::seed_from_u64(seed)),
+            RngKind::Pcg64 => AnyRng::Pcg64(Pcg64::seed_from_u64(seed)),
+            RngKind::ChaCha8 => AnyRng::ChaCha8(ChaCha8Rng::seed_from_u64(seed)),
+            RngKind::ChaCha20 => AnyRng::ChaCha20(ChaCha20Rng::seed_from_u64(seed)),
+            RngKind::WyRand => AnyRng::WyRand(WyRand::seed_from_u64(seed)),
+            RngKind::SplitMix => AnyRng::SplitMix(SplitMix64::seed_from_u64(seed)),
+        }
+    }
+}
+
+/// Parses the names printed by [Display](std::fmt::Display).
+///
+/// ```rust
+/// use monty_pyrs::RngKind;
+///
+/// assert_eq!("pcg64".parse(), Ok(RngKind::Pcg64));
+/// assert!("mersenne".parse::<RngKind>().is_err());
+/// ```
+impl FromStr for RngKind {
+    type Err = MontyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RngKind::ALL
+            .iter()
+            .copied()
+            .find(|kind| kind.to_string() == s)
+            .ok_or_else(|| MontyError::UnknownRng(s.to_string()))
+    }
+}
+
+/// Any of the generators named by [RngKind], for when the kind is only known
+/// at runtime. The [Simulator](crate::Simulator) plays with the generators
+/// themselves, which is faster.
+#[derive(Debug, Clone)]
+pub enum AnyRng {
+    XorShift(XorShiftRng),
+    Pcg64(Pcg64),
+    ChaCha8(ChaCha8Rng),
+    ChaCha20(ChaCha20Rng),
+    WyRand(WyRand),
+    SplitMix(SplitMix64),
+}
+
+/// Forwards a call to whichever generator is wrapped.
+macro_rules! delegate {
+    ($rng:expr, $inner:ident => $call:expr) => {
+        match $rng {
+            AnyRng::XorShift($inner) => $call,
+            AnyRng::Pcg64($inner) => $call,
+            AnyRng::ChaCha8($inner) => $call,
+            AnyRng::ChaCha20($inner) => $call,
+            AnyRng::WyRand($inner) => $call,
+            AnyRng::SplitMix($inner) => $call,
+        }
+    };
+}
+
+impl RngCore for AnyRng {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        delegate!(self, rng => rng.next_u32())
+    }
+
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        delegate!(self, rng => rng.next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        delegate!(self, rng => rng.fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        delegate!(self, rng => rng.try_fill_bytes(dest))
+    }
+}
+
+/// The [wyrand](https://github.com/wangyi-fudan/wyhash) generator: a 64-bit
+/// counter scrambled by a wide multiplication.
+#[derive(Debug, Clone)]
+pub struct WyRand {
+    state: u64,
+}
+
+impl RngCore for WyRand {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0xa076_1d64_78bd_642f);
+        let product = u128::from(self.state) * u128::from(self.state ^ 0xe703_7ed1_a0b4_28db);
+        ((product >> 64) ^ product) as u64
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl SeedableRng for WyRand {
+    type Seed = [u8; 8];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        Self {
+            state: u64::from_le_bytes(seed),
+        }
+    }
+}
+
+/// The [SplitMix64](https://prng.di.unimi.it/splitmix64.c) generator.
+#[derive(Debug, Clone)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl RngCore for SplitMix64 {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        let output = splitmix64(self.state);
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        output
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl SeedableRng for SplitMix64 {
+    type Seed = [u8; 8];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        Self {
+            state: u64::from_le_bytes(seed),
+        }
+    }
+}
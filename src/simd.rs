@@ -0,0 +1,659 @@
+//! Vectorized backends for the bit-parallel kernel, chosen at runtime from
+//! the features of the CPU.
+//!
+//! The vectorized backends play 512 games per step: 8 words of 64 games,
+//! each word drawn from its own [xoshiro256**](https://prng.di.unimi.it/)
+//! generator. The generators are seeded from the generator of the
+//! [MontyHall](crate::MontyHall), so runs stay reproducible from their seed.
+//! As the games aren't played with the chosen generator itself, a
+//! [Simulator](crate::Simulator) only uses the vectorized backends with the
+//! default [RngKind::XorShift]:
+//!
+//! ```rust
+//! use monty_pyrs::{Backend, RngKind, Simulator};
+//!
+//! let simulator = Simulator::builder().rng(RngKind::ChaCha20).build().unwrap();
+//! assert_eq!(simulator.backend(), Backend::Scalar);
+//! let builder = Simulator::builder().rng(RngKind::ChaCha20).backend(Backend::Portable);
+//! assert!(builder.build().is_err());
+//! ```
+//!
+//! All vectorized backends run the same computation on differently sized
+//! registers and give exactly the same results, so they can be compared on
+//! the same machine:
+//!
+//! ```rust
+//! use monty_pyrs::{Backend, GameConfig, MontyHall, Player};
+//!
+//! let config = GameConfig::new(10, 3).unwrap();
+//! let play = |backend| {
+//!     let mut monty = MontyHall::seeded(9).with_backend(backend).unwrap();
+//!     monty.play_multiple(&config, &Player::CLASSIC, 1_000_003).unwrap()
+//! };
+//! let portable = play(Backend::Portable);
+//! for backend in Backend::ALL {
+//!     if backend.is_supported() && backend != Backend::Scalar {
+//!         assert_eq!(play(backend), portable, "{} differs", backend);
+//!     }
+//! }
+//! ```
+
+use crate::kernel::Kernel;
+use crate::{MontyError, ResultSet, RngKind};
+use derive_more::Display;
+use rand_core::RngCore;
+use std::str::FromStr;
+
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
+/// How [MontyHall](crate::MontyHall) plays the games the bit-parallel kernel covers.
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Backend {
+    /// The fastest backend the CPU supports, or [Backend::Scalar] for
+    /// generators other than [RngKind::XorShift].
+    #[default]
+    #[display(fmt = "auto")]
+    Auto,
+    /// One word of 64 games at a time, drawn from the generator of the
+    /// [MontyHall](crate::MontyHall) itself. Unlike the vectorized backends,
+    /// this plays with the chosen generator rather than just seeding from it.
+    #[display(fmt = "scalar")]
+    Scalar,
+    /// The vectorized computation in plain Rust, for any CPU.
+    #[display(fmt = "portable")]
+    Portable,
+    /// 128-bit SSE2 registers, available on every x86_64 CPU.
+    #[display(fmt = "sse2")]
+    Sse2,
+    /// 256-bit AVX2 registers.
+    #[display(fmt = "avx2")]
+    Avx2,
+    /// 512-bit AVX-512 registers.
+    #[display(fmt = "avx512")]
+    Avx512,
+}
+
+impl Backend {
+    /// Every backend, [Backend::Auto] first and the others from slowest to fastest.
+    pub const ALL: [Backend; 6] = [
+        Backend::Auto,
+        Backend::Scalar,
+        Backend::Portable,
+        Backend::Sse2,
+        Backend::Avx2,
+        Backend::Avx512,
+    ];
+
+    /// Whether the CPU can run this backend.
+    pub fn is_supported(self) -> bool {
+        match self {
+            Backend::Auto | Backend::Scalar | Backend::Portable => true,
+            #[cfg(target_arch = "x86_64")]
+            Backend::Sse2 => is_x86_feature_detected!("sse2"),
+            #[cfg(target_arch = "x86_64")]
+            Backend::Avx2 => is_x86_feature_detected!("avx2"),
+            #[cfg(target_arch = "x86_64")]
+            Backend::Avx512 => is_x86_feature_detected!("avx512f"),
+            #[cfg(not(target_arch = "x86_64"))]
+            Backend::Sse2 | Backend::Avx2 | Backend::Avx512 => false,
+        }
+    }
+
+    /// The fastest backend the CPU supports.
+    pub fn detect() -> Backend {
+        Backend::ALL
+            .iter()
+            .rev()
+            .copied()
+            .find(|backend| *backend != Backend::Auto && backend.is_supported())
+            .unwrap_or(Backend::Portable)
+    }
+
+    /// The backend to play with: the detected one for [Backend::Auto], or
+    /// this one if the CPU supports it.
+    pub(crate) fn resolve(self) -> Result<Backend, MontyError> {
+        match self {
+            Backend::Auto => Ok(Backend::detect()),
+            backend if backend.is_supported() => Ok(backend),
+            backend => Err(MontyError::UnsupportedBackend(backend.to_string())),
+        }
+    }
+
+    /// Like [Backend::resolve], for games played with the `rng` generator.
+    /// The vectorized backends only seed their generators from it, so they
+    /// are only used with [RngKind::XorShift].
+    pub(crate) fn resolve_for(self, rng: RngKind) -> Result<Backend, MontyError> {
+        match self {
+            _ if rng == RngKind::XorShift => self.resolve(),
+            Backend::Auto | Backend::Scalar => Ok(Backend::Scalar),
+            backend => Err(MontyError::IncompatibleBackend(
+                backend.to_string(),
+                rng.to_string(),
+            )),
+        }
+    }
+}
+
+/// Parses the names printed by [Display](std::fmt::Display).
+///
+/// ```rust
+/// use monty_pyrs::Backend;
+///
+/// assert_eq!("avx2".parse(), Ok(Backend::Avx2));
+/// assert!("neon".parse::<Backend>().is_err());
+/// ```
+impl FromStr for Backend {
+    type Err = MontyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Backend::ALL
+            .iter()
+            .copied()
+            .find(|backend| backend.to_string() == s)
+            .ok_or_else(|| MontyError::UnknownBackend(s.to_string()))
+    }
+}
+
+/// The most words any backend plays per step.
+const MAX_WORDS: usize = 8;
+
+/// A register of 64-bit words, each holding the bits of 64 games.
+///
+/// # Safety
+///
+/// The x86_64 implementations call intrinsics without checking for the CPU
+/// features, so the methods must only be called from functions enabling
+/// those features.
+pub(crate) trait Lanes: Copy {
+    /// The number of 64-bit words.
+    const WORDS: usize;
+
+    unsafe fn splat(word: u64) -> Self;
+    /// Load the first [Lanes::WORDS] words.
+    unsafe fn load(words: &[u64]) -> Self;
+    /// Store into the first [Lanes::WORDS] words.
+    unsafe fn store(self, words: &mut [u64]);
+    unsafe fn and(self, other: Self) -> Self;
+    unsafe fn or(self, other: Self) -> Self;
+    unsafe fn xor(self, other: Self) -> Self;
+    /// `self & !other`
+    unsafe fn and_not(self, other: Self) -> Self;
+    /// Wrapping addition of every word.
+    unsafe fn add(self, other: Self) -> Self;
+    unsafe fn shl(self, bits: u32) -> Self;
+    unsafe fn shr(self, bits: u32) -> Self;
+    /// Whether any bit is set.
+    unsafe fn any(self) -> bool;
+
+    #[inline(always)]
+    unsafe fn rotl(self, bits: u32) -> Self {
+        self.shl(bits).or(self.shr(64 - bits))
+    }
+
+    /// The number of set bits across all words.
+    #[inline(always)]
+    unsafe fn count_ones(self) -> u64 {
+        let mut words = [0; MAX_WORDS];
+        self.store(&mut words);
+        words[..Self::WORDS]
+            .iter()
+            .map(|word| u64::from(word.count_ones()))
+            .sum()
+    }
+
+    /// The bits of the first `games` games, counting word by word.
+    #[inline(always)]
+    unsafe fn first_games(games: u64) -> Self {
+        let mut words = [0; MAX_WORDS];
+        for (index, word) in words[..Self::WORDS].iter_mut().enumerate() {
+            *word = match games.saturating_sub(64 * index as u64) {
+                0 => 0,
+                bits if bits >= 64 => !0,
+                bits => !0 >> (64 - bits),
+            };
+        }
+        Self::load(&words)
+    }
+}
+
+/// The scalar backend, a single word.
+impl Lanes for u64 {
+    const WORDS: usize = 1;
+
+    #[inline(always)]
+    unsafe fn splat(word: u64) -> Self {
+        word
+    }
+    #[inline(always)]
+    unsafe fn load(words: &[u64]) -> Self {
+        words[0]
+    }
+    #[inline(always)]
+    unsafe fn store(self, words: &mut [u64]) {
+        words[0] = self;
+    }
+    #[inline(always)]
+    unsafe fn and(self, other: Self) -> Self {
+        self & other
+    }
+    #[inline(always)]
+    unsafe fn or(self, other: Self) -> Self {
+        self | other
+    }
+    #[inline(always)]
+    unsafe fn xor(self, other: Self) -> Self {
+        self ^ other
+    }
+    #[inline(always)]
+    unsafe fn and_not(self, other: Self) -> Self {
+        self & !other
+    }
+    #[inline(always)]
+    unsafe fn add(self, other: Self) -> Self {
+        self.wrapping_add(other)
+    }
+    #[inline(always)]
+    unsafe fn shl(self, bits: u32) -> Self {
+        self << bits
+    }
+    #[inline(always)]
+    unsafe fn shr(self, bits: u32) -> Self {
+        self >> bits
+    }
+    #[inline(always)]
+    unsafe fn any(self) -> bool {
+        self != 0
+    }
+    #[inline(always)]
+    unsafe fn count_ones(self) -> u64 {
+        u64::from(u64::count_ones(self))
+    }
+}
+
+/// The portable backend, left to the compiler to vectorize.
+#[derive(Clone, Copy)]
+pub(crate) struct Portable([u64; MAX_WORDS]);
+
+impl Portable {
+    #[inline(always)]
+    fn map(self, other: Self, op: impl Fn(u64, u64) -> u64) -> Self {
+        let mut words = self.0;
+        for (word, other) in words.iter_mut().zip(other.0) {
+            *word = op(*word, other);
+        }
+        Self(words)
+    }
+}
+
+impl Lanes for Portable {
+    const WORDS: usize = MAX_WORDS;
+
+    #[inline(always)]
+    unsafe fn splat(word: u64) -> Self {
+        Self([word; MAX_WORDS])
+    }
+    #[inline(always)]
+    unsafe fn load(words: &[u64]) -> Self {
+        let mut loaded = [0; MAX_WORDS];
+        loaded.copy_from_slice(&words[..MAX_WORDS]);
+        Self(loaded)
+    }
+    #[inline(always)]
+    unsafe fn store(self, words: &mut [u64]) {
+        words[..MAX_WORDS].copy_from_slice(&self.0);
+    }
+    #[inline(always)]
+    unsafe fn and(self, other: Self) -> Self {
+        self.map(other, |a, b| a & b)
+    }
+    #[inline(always)]
+    unsafe fn or(self, other: Self) -> Self {
+        self.map(other, |a, b| a | b)
+    }
+    #[inline(always)]
+    unsafe fn xor(self, other: Self) -> Self {
+        self.map(other, |a, b| a ^ b)
+    }
+    #[inline(always)]
+    unsafe fn and_not(self, other: Self) -> Self {
+        self.map(other, |a, b| a & !b)
+    }
+    #[inline(always)]
+    unsafe fn add(self, other: Self) -> Self {
+        self.map(other, u64::wrapping_add)
+    }
+    #[inline(always)]
+    unsafe fn shl(self, bits: u32) -> Self {
+        self.map(self, |a, _| a << bits)
+    }
+    #[inline(always)]
+    unsafe fn shr(self, bits: u32) -> Self {
+        self.map(self, |a, _| a >> bits)
+    }
+    #[inline(always)]
+    unsafe fn any(self) -> bool {
+        self.0.iter().fold(0, |any, word| any | word) != 0
+    }
+}
+
+/// Four 128-bit SSE2 registers.
+#[cfg(target_arch = "x86_64")]
+#[derive(Clone, Copy)]
+pub(crate) struct Sse2([__m128i; 4]);
+
+#[cfg(target_arch = "x86_64")]
+impl Sse2 {
+    #[inline(always)]
+    fn map(self, other: Self, op: impl Fn(__m128i, __m128i) -> __m128i) -> Self {
+        let mut registers = self.0;
+        for (register, other) in registers.iter_mut().zip(other.0) {
+            *register = op(*register, other);
+        }
+        Self(registers)
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+impl Lanes for Sse2 {
+    const WORDS: usize = 8;
+
+    #[inline(always)]
+    unsafe fn splat(word: u64) -> Self {
+        Self([unsafe { _mm_set1_epi64x(word as i64) }; 4])
+    }
+    #[inline(always)]
+    unsafe fn load(words: &[u64]) -> Self {
+        assert!(words.len() >= Self::WORDS);
+        let pointer = words.as_ptr() as *const __m128i;
+        Self(std::array::from_fn(|index| unsafe {
+            _mm_loadu_si128(pointer.add(index))
+        }))
+    }
+    #[inline(always)]
+    unsafe fn store(self, words: &mut [u64]) {
+        assert!(words.len() >= Self::WORDS);
+        let pointer = words.as_mut_ptr() as *mut __m128i;
+        for (index, register) in self.0.iter().enumerate() {
+            unsafe { _mm_storeu_si128(pointer.add(index), *register) };
+        }
+    }
+    #[inline(always)]
+    unsafe fn and(self, other: Self) -> Self {
+        self.map(other, |a, b| unsafe { _mm_and_si128(a, b) })
+    }
+    #[inline(always)]
+    unsafe fn or(self, other: Self) -> Self {
+        self.map(other, |a, b| unsafe { _mm_or_si128(a, b) })
+    }
+    #[inline(always)]
+    unsafe fn xor(self, other: Self) -> Self {
+        self.map(other, |a, b| unsafe { _mm_xor_si128(a, b) })
+    }
+    #[inline(always)]
+    unsafe fn and_not(self, other: Self) -> Self {
+        // The intrinsic negates its first operand
+        self.map(other, |a, b| unsafe { _mm_andnot_si128(b, a) })
+    }
+    #[inline(always)]
+    unsafe fn add(self, other: Self) -> Self {
+        self.map(other, |a, b| unsafe { _mm_add_epi64(a, b) })
+    }
+    #[inline(always)]
+    unsafe fn shl(self, bits: u32) -> Self {
+        self.map(self, |a, _| unsafe {
+            _mm_sll_epi64(a, _mm_cvtsi32_si128(bits as i32))
+        })
+    }
+    #[inline(always)]
+    unsafe fn shr(self, bits: u32) -> Self {
+        self.map(self, |a, _| unsafe {
+            _mm_srl_epi64(a, _mm_cvtsi32_si128(bits as i32))
+        })
+    }
+    #[inline(always)]
+    unsafe fn any(self) -> bool {
+        let [a, b, c, d] = self.0;
+        unsafe {
+            let any = _mm_or_si128(_mm_or_si128(a, b), _mm_or_si128(c, d));
+            _mm_movemask_epi8(_mm_cmpeq_epi8(any, _mm_setzero_si128())) != 0xffff
+        }
+    }
+}
+
+/// Two 256-bit AVX2 registers.
+#[cfg(target_arch = "x86_64")]
+#[derive(Clone, Copy)]
+pub(crate) struct Avx2([__m256i; 2]);
+
+#[cfg(target_arch = "x86_64")]
+impl Avx2 {
+    #[inline(always)]
+    fn map(self, other: Self, op: impl Fn(__m256i, __m256i) -> __m256i) -> Self {
+        let [a, b] = self.0;
+        let [c, d] = other.0;
+        Self([op(a, c), op(b, d)])
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+impl Lanes for Avx2 {
+    const WORDS: usize = 8;
+
+    #[inline(always)]
+    unsafe fn splat(word: u64) -> Self {
+        Self([unsafe { _mm256_set1_epi64x(word as i64) }; 2])
+    }
+    #[inline(always)]
+    unsafe fn load(words: &[u64]) -> Self {
+        assert!(words.len() >= Self::WORDS);
+        let pointer = words.as_ptr() as *const __m256i;
+        unsafe {
+            Self([
+                _mm256_loadu_si256(pointer),
+                _mm256_loadu_si256(pointer.add(1)),
+            ])
+        }
+    }
+    #[inline(always)]
+    unsafe fn store(self, words: &mut [u64]) {
+        assert!(words.len() >= Self::WORDS);
+        let pointer = words.as_mut_ptr() as *mut __m256i;
+        unsafe {
+            _mm256_storeu_si256(pointer, self.0[0]);
+            _mm256_storeu_si256(pointer.add(1), self.0[1]);
+        }
+    }
+    #[inline(always)]
+    unsafe fn and(self, other: Self) -> Self {
+        self.map(other, |a, b| unsafe { _mm256_and_si256(a, b) })
+    }
+    #[inline(always)]
+    unsafe fn or(self, other: Self) -> Self {
+        self.map(other, |a, b| unsafe { _mm256_or_si256(a, b) })
+    }
+    #[inline(always)]
+    unsafe fn xor(self, other: Self) -> Self {
+        self.map(other, |a, b| unsafe { _mm256_xor_si256(a, b) })
+    }
+    #[inline(always)]
+    unsafe fn and_not(self, other: Self) -> Self {
+        // The intrinsic negates its first operand
+        self.map(other, |a, b| unsafe { _mm256_andnot_si256(b, a) })
+    }
+    #[inline(always)]
+    unsafe fn add(self, other: Self) -> Self {
+        self.map(other, |a, b| unsafe { _mm256_add_epi64(a, b) })
+    }
+    #[inline(always)]
+    unsafe fn shl(self, bits: u32) -> Self {
+        self.map(self, |a, _| unsafe {
+            _mm256_sll_epi64(a, _mm_cvtsi32_si128(bits as i32))
+        })
+    }
+    #[inline(always)]
+    unsafe fn shr(self, bits: u32) -> Self {
+        self.map(self, |a, _| unsafe {
+            _mm256_srl_epi64(a, _mm_cvtsi32_si128(bits as i32))
+        })
+    }
+    #[inline(always)]
+    unsafe fn any(self) -> bool {
+        let [a, b] = self.0;
+        unsafe {
+            let any = _mm256_or_si256(a, b);
+            _mm256_testz_si256(any, any) == 0
+        }
+    }
+}
+
+/// A single 512-bit AVX-512 register.
+#[cfg(target_arch = "x86_64")]
+#[derive(Clone, Copy)]
+pub(crate) struct Avx512(__m512i);
+
+#[cfg(target_arch = "x86_64")]
+impl Lanes for Avx512 {
+    const WORDS: usize = 8;
+
+    #[inline(always)]
+    unsafe fn splat(word: u64) -> Self {
+        Self(unsafe { _mm512_set1_epi64(word as i64) })
+    }
+    #[inline(always)]
+    unsafe fn load(words: &[u64]) -> Self {
+        assert!(words.len() >= Self::WORDS);
+        Self(unsafe { _mm512_loadu_si512(words.as_ptr() as *const __m512i) })
+    }
+    #[inline(always)]
+    unsafe fn store(self, words: &mut [u64]) {
+        assert!(words.len() >= Self::WORDS);
+        unsafe { _mm512_storeu_si512(words.as_mut_ptr() as *mut __m512i, self.0) };
+    }
+    #[inline(always)]
+    unsafe fn and(self, other: Self) -> Self {
+        Self(unsafe { _mm512_and_si512(self.0, other.0) })
+    }
+    #[inline(always)]
+    unsafe fn or(self, other: Self) -> Self {
+        Self(unsafe { _mm512_or_si512(self.0, other.0) })
+    }
+    #[inline(always)]
+    unsafe fn xor(self, other: Self) -> Self {
+        Self(unsafe { _mm512_xor_si512(self.0, other.0) })
+    }
+    #[inline(always)]
+    unsafe fn and_not(self, other: Self) -> Self {
+        // The intrinsic negates its first operand
+        Self(unsafe { _mm512_andnot_si512(other.0, self.0) })
+    }
+    #[inline(always)]
+    unsafe fn add(self, other: Self) -> Self {
+        Self(unsafe { _mm512_add_epi64(self.0, other.0) })
+    }
+    #[inline(always)]
+    unsafe fn shl(self, bits: u32) -> Self {
+        Self(unsafe { _mm512_sll_epi64(self.0, _mm_cvtsi32_si128(bits as i32)) })
+    }
+    #[inline(always)]
+    unsafe fn shr(self, bits: u32) -> Self {
+        Self(unsafe { _mm512_srl_epi64(self.0, _mm_cvtsi32_si128(bits as i32)) })
+    }
+    #[inline(always)]
+    unsafe fn any(self) -> bool {
+        unsafe { _mm512_test_epi64_mask(self.0, self.0) != 0 }
+    }
+}
+
+/// The state of the xoshiro256** generators of the vectorized backends, one
+/// generator per word.
+#[derive(Debug, Clone)]
+pub(crate) struct LaneRng {
+    state: [[u64; MAX_WORDS]; 4],
+}
+
+impl LaneRng {
+    /// Seed every generator from `rng`.
+    pub(crate) fn from_rng<R: RngCore + ?Sized>(rng: &mut R) -> Self {
+        let mut state = [[0; MAX_WORDS]; 4];
+        for word in state.iter_mut().flatten() {
+            *word = rng.next_u64();
+        }
+        // xoshiro is stuck at an all-zero state
+        for lane in 0..MAX_WORDS {
+            if state.iter().all(|words| words[lane] == 0) {
+                state[0][lane] = 0x9e37_79b9_7f4a_7c15;
+            }
+        }
+        Self { state }
+    }
+
+    /// Play `games` games of `kernel` with registers of type `V`, keeping the
+    /// generators in registers while playing.
+    ///
+    /// # Safety
+    ///
+    /// The CPU features `V` needs must be enabled, see [Lanes].
+    #[inline(always)]
+    unsafe fn play<V: Lanes>(&mut self, kernel: &Kernel, games: u64) -> ResultSet {
+        let [s0, s1, s2, s3] = &self.state;
+        let mut state = [V::load(s0), V::load(s1), V::load(s2), V::load(s3)];
+        let results = kernel.play_lanes(
+            #[inline(always)]
+            || {
+                let [s0, s1, s2, s3] = &mut state;
+                // x * 5 and x * 9 as shifts and adds, which every backend has
+                let times_five = s1.add(s1.shl(2));
+                let rotated = times_five.rotl(7);
+                let result = rotated.add(rotated.shl(3));
+                let t = s1.shl(17);
+                *s2 = s2.xor(*s0);
+                *s3 = s3.xor(*s1);
+                *s1 = s1.xor(*s2);
+                *s0 = s0.xor(*s3);
+                *s2 = s2.xor(t);
+                *s3 = s3.rotl(45);
+                result
+            },
+            games,
+        );
+        for (words, register) in self.state.iter_mut().zip(state) {
+            register.store(words);
+        }
+        results
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn play_sse2(rng: &mut LaneRng, kernel: &Kernel, games: u64) -> ResultSet {
+    rng.play::<Sse2>(kernel, games)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn play_avx2(rng: &mut LaneRng, kernel: &Kernel, games: u64) -> ResultSet {
+    rng.play::<Avx2>(kernel, games)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f")]
+unsafe fn play_avx512(rng: &mut LaneRng, kernel: &Kernel, games: u64) -> ResultSet {
+    rng.play::<Avx512>(kernel, games)
+}
+
+/// Play `games` games of `kernel` with one of the vectorized backends, which
+/// must have been resolved and found supported.
+pub(crate) fn play(backend: Backend, rng: &mut LaneRng, kernel: &Kernel, games: u64) -> ResultSet {
+    match backend {
+        #[cfg(target_arch = "x86_64")]
+        Backend::Sse2 => unsafe { play_sse2(rng, kernel, games) },
+        #[cfg(target_arch = "x86_64")]
+        Backend::Avx2 => unsafe { play_avx2(rng, kernel, games) },
+        #[cfg(target_arch = "x86_64")]
+        Backend::Avx512 => unsafe { play_avx512(rng, kernel, games) },
+        // Plain Rust needs no CPU features
+        Backend::Portable => unsafe { rng.play::<Portable>(kernel, games) },
+        _ => unreachable!("{} is not a supported vectorized backend", backend),
+    }
+}
@@ -0,0 +1,687 @@
+//! A reusable, configurable engine for running simulations across threads.
+
+use crate::rng::{self, SplitMix64, WyRand};
+use crate::{
+    check_iterations, check_probability, stats, Backend, Fidelity, GameConfig, MontyError,
+    MontyHall, Player, Results, RngKind, RunMetadata, Sweep, SweepResults,
+};
+use rand_chacha::{ChaCha20Rng, ChaCha8Rng};
+use rand_core::{RngCore, SeedableRng};
+use rand_pcg::Pcg64;
+use rand_xorshift::XorShiftRng;
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The number of games played with each independently seeded random number
+/// generator when running threaded simulations.
+const CHUNK_SIZE: u64 = 1 << 16;
+
+/// How many worker pools of different sizes are kept alive between simulators.
+const CACHED_POOLS: usize = 4;
+
+/// The worker pool for `threads` threads, shared between all simulators using
+/// the same number of threads. The pools of the [CACHED_POOLS] most recently
+/// used sizes are kept alive for the lifetime of the process. Older ones stop
+/// once the last simulator using them is dropped, so that trying many thread
+/// counts doesn't leave their threads running.
+fn worker_pool(threads: usize) -> Result<Arc<ThreadPool>, MontyError> {
+    // Least recently used first
+    static POOLS: Mutex<Vec<(usize, Arc<ThreadPool>)>> = Mutex::new(Vec::new());
+    let mut pools = POOLS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(position) = pools.iter().position(|(size, _)| *size == threads) {
+        let entry = pools.remove(position);
+        let pool = Arc::clone(&entry.1);
+        pools.push(entry);
+        return Ok(pool);
+    }
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .thread_name(|index| format!("monty-worker-{}", index))
+        .build()
+        .map_err(|err| MontyError::ThreadPool(err.to_string()))?;
+    let pool = Arc::new(pool);
+    if pools.len() == CACHED_POOLS {
+        pools.remove(0);
+    }
+    pools.push((threads, Arc::clone(&pool)));
+    Ok(pool)
+}
+
+/// A handle for stopping running simulations from another thread.
+///
+/// Clones share the same state, so cancelling one cancels them all.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask the simulations using this token to stop.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether [CancellationToken::cancel] has been called.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// A snapshot of a running simulation, passed to the callback of
+/// [Simulator::play_with_progress].
+#[derive(Debug, Clone)]
+pub struct Progress {
+    iterations: u64,
+    elapsed: Duration,
+    results: Results,
+    finished: bool,
+}
+
+impl Progress {
+    /// The number of games played so far, across all worker threads.
+    pub fn games_played(&self) -> u64 {
+        self.results.total_games()
+    }
+
+    /// The number of games requested.
+    pub fn iterations(&self) -> u64 {
+        self.iterations
+    }
+
+    /// The time since the simulation started.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// The win rates of the strategies over the games played so far.
+    pub fn win_rates(&self) -> Vec<(String, Option<f64>)> {
+        self.results.win_rates()
+    }
+
+    /// The results of the games played so far.
+    pub fn results(&self) -> &Results {
+        &self.results
+    }
+
+    /// Whether this is the final report, sent once the simulation has finished.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+/// Builds a [Simulator].
+#[derive(Debug, Clone, Default)]
+pub struct SimulatorBuilder {
+    threads: Option<usize>,
+    seed: u64,
+    fidelity: Fidelity,
+    rng: RngKind,
+    backend: Backend,
+}
+
+impl SimulatorBuilder {
+    /// The number of worker threads. Defaults to the number of logical CPUs.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+
+    /// The seed all random number generators are derived from. Defaults to 0.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// How faithfully games are simulated. Defaults to [Fidelity::Fast].
+    pub fn fidelity(mut self, fidelity: Fidelity) -> Self {
+        self.fidelity = fidelity;
+        self
+    }
+
+    /// The random number generator games are played with. Defaults to [RngKind::XorShift].
+    pub fn rng(mut self, rng: RngKind) -> Self {
+        self.rng = rng;
+        self
+    }
+
+    /// How the games covered by the bit-parallel kernel are played. Defaults
+    /// to [Backend::Auto]. The vectorized backends can only be chosen with
+    /// [RngKind::XorShift].
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Create the simulator, starting its worker threads unless a recent
+    /// simulator with the same number of threads already started them.
+    ///
+    /// Fails if there are no threads, or the CPU or generator doesn't support
+    /// the backend.
+    pub fn build(self) -> Result<Simulator, MontyError> {
+        let threads = match self.threads {
+            Some(0) => return Err(MontyError::InvalidThreads),
+            Some(threads) => threads,
+            None => num_cpus::get(),
+        };
+        let backend = self.backend.resolve_for(self.rng)?;
+        Ok(Simulator {
+            pool: worker_pool(threads)?,
+            threads,
+            seed: self.seed,
+            fidelity: self.fidelity,
+            rng: self.rng,
+            backend,
+        })
+    }
+}
+
+/// Runs simulations on a persistent pool of worker threads.
+///
+/// The games are played in fixed-size chunks, each with its own generator
+/// seeded from the seed of the simulator and the position of the chunk. Every
+/// chunk therefore plays a distinct sequence of games, and the results only
+/// depend on the seed, not on the number of threads.
+///
+/// ```rust
+/// use monty_pyrs::{GameConfig, Player, Simulator};
+///
+/// let config = GameConfig::default();
+/// let single = Simulator::builder().threads(1).seed(42).build().unwrap();
+/// let awkward = Simulator::builder().threads(7).seed(42).build().unwrap();
+///
+/// let results = awkward.play(&config, &Player::CLASSIC, 1_000_003).unwrap();
+/// assert_eq!(results.total_games(), 1_000_003);
+/// let expected = single.play(&config, &Player::CLASSIC, 1_000_003).unwrap();
+/// assert_eq!(results.result_sets(), expected.result_sets());
+/// ```
+#[derive(Clone)]
+pub struct Simulator {
+    pool: Arc<ThreadPool>,
+    threads: usize,
+    seed: u64,
+    fidelity: Fidelity,
+    rng: RngKind,
+    /// Resolved, never [Backend::Auto]
+    backend: Backend,
+}
+
+impl Simulator {
+    /// Start configuring a simulator.
+    pub fn builder() -> SimulatorBuilder {
+        SimulatorBuilder::default()
+    }
+
+    /// The number of worker threads.
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// The seed all random number generators are derived from.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The random number generator games are played with.
+    pub fn rng(&self) -> RngKind {
+        self.rng
+    }
+
+    /// The backend playing the games covered by the bit-parallel kernel,
+    /// with [Backend::Auto] resolved to the detected one.
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    /// Play exactly `iterations` games, split between the strategies as in
+    /// [MontyHall::play_multiple].
+    pub fn play(
+        &self,
+        config: &GameConfig,
+        players: &[Player],
+        iterations: u64,
+    ) -> Result<Results, MontyError> {
+        self.play_cancellable(config, players, iterations, &CancellationToken::new())
+    }
+
+    /// Like [Simulator::play], but sample the results from their exact
+    /// distribution with [MontyHall::play_aggregate] instead of playing the
+    /// games, on the calling thread and in constant time.
+    ///
+    /// ```rust
+    /// use monty_pyrs::{GameConfig, Player, RngKind, Simulator};
+    ///
+    /// let simulator = Simulator::builder().seed(3).rng(RngKind::Pcg64).build().unwrap();
+    /// let results = simulator.play_aggregate(&GameConfig::default(), &Player::CLASSIC, u64::MAX).unwrap();
+    /// assert_eq!(results, simulator.play_aggregate(&GameConfig::default(), &Player::CLASSIC, u64::MAX).unwrap());
+    /// assert_eq!(results.metadata()[0].seed(), Some(3));
+    /// assert!(results.is_aggregate());
+    /// ```
+    pub fn play_aggregate(
+        &self,
+        config: &GameConfig,
+        players: &[Player],
+        iterations: u64,
+    ) -> Result<Results, MontyError> {
+        MontyHall::seeded_with(self.rng, self.seed)
+            .with_fidelity(self.fidelity)
+            .play_aggregate(config, players, iterations)
+    }
+
+    /// Like [Simulator::play], but stops early once `token` is cancelled,
+    /// returning the results of the games played so far, marked as incomplete.
+    ///
+    /// ```rust
+    /// use monty_pyrs::{CancellationToken, GameConfig, Player, Simulator};
+    /// use std::{thread, time::Duration};
+    ///
+    /// let simulator = Simulator::builder().build().unwrap();
+    /// let token = CancellationToken::new();
+    /// let canceller = token.clone();
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_millis(100));
+    ///     canceller.cancel();
+    /// });
+    /// let results = simulator.play_cancellable(&GameConfig::default(), &Player::CLASSIC, u64::MAX, &token).unwrap();
+    /// assert!(!results.is_complete());
+    /// assert!(results.total_games() > 0);
+    /// ```
+    pub fn play_cancellable(
+        &self,
+        config: &GameConfig,
+        players: &[Player],
+        iterations: u64,
+        token: &CancellationToken,
+    ) -> Result<Results, MontyError> {
+        check_iterations(players, iterations)?;
+        Ok(self.play_chunks(config, players, 0..iterations, self.seed, token, None))
+    }
+
+    /// Like [Simulator::play_cancellable], but calls `on_progress` from the
+    /// calling thread every `interval` while the simulation runs, and once more
+    /// when it has finished.
+    ///
+    /// ```rust
+    /// use monty_pyrs::{CancellationToken, GameConfig, Player, Simulator};
+    /// use std::time::Duration;
+    ///
+    /// let simulator = Simulator::builder().build().unwrap();
+    /// let mut reports = Vec::new();
+    /// let results = simulator.play_with_progress(
+    ///     &GameConfig::default(),
+    ///     &Player::CLASSIC,
+    ///     20_000_000,
+    ///     &CancellationToken::new(),
+    ///     Duration::from_millis(10),
+    ///     |progress| reports.push(progress.games_played()),
+    /// ).unwrap();
+    /// assert!(reports.windows(2).all(|pair| pair[0] <= pair[1]));
+    /// assert_eq!(reports.last(), Some(&results.total_games()));
+    /// ```
+    pub fn play_with_progress<F>(
+        &self,
+        config: &GameConfig,
+        players: &[Player],
+        iterations: u64,
+        token: &CancellationToken,
+        interval: Duration,
+        mut on_progress: F,
+    ) -> Result<Results, MontyError>
+    where
+        F: FnMut(&Progress),
+    {
+        check_iterations(players, iterations)?;
+        let started = Instant::now();
+        let running = Mutex::new(Results::default());
+        let (finished, is_finished) = mpsc::channel();
+        let results = std::thread::scope(|scope| {
+            let worker = scope.spawn(|| {
+                let results = self.play_chunks(
+                    config,
+                    players,
+                    0..iterations,
+                    self.seed,
+                    token,
+                    Some(&running),
+                );
+                let _ = finished.send(());
+                results
+            });
+            while let Err(RecvTimeoutError::Timeout) = is_finished.recv_timeout(interval) {
+                let results = running
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .clone();
+                on_progress(&Progress {
+                    iterations,
+                    elapsed: started.elapsed(),
+                    results,
+                    finished: false,
+                });
+            }
+            worker
+                .join()
+                .unwrap_or_else(|payload| std::panic::resume_unwind(payload))
+        });
+        on_progress(&Progress {
+            iterations,
+            elapsed: started.elapsed(),
+            results: results.clone(),
+            finished: true,
+        });
+        Ok(results)
+    }
+
+    /// Play until the win rate of every strategy is known to within
+    /// `precision` either way at the `confidence` level, or until
+    /// `max_iterations` games have been played. Returns the results along
+    /// with the number of games played.
+    ///
+    /// The games are played in rounds, each sized from the win rates seen so
+    /// far, and the Wilson intervals of the strategies are checked after every
+    /// round. Checking repeatedly gives chance more opportunities to stop the
+    /// run on a misleadingly narrow interval, so round `k` checks at the level
+    /// `1 - (1 - confidence) / (k (k + 1))`, shared between the strategies. The
+    /// levels add up such that however many rounds are played, the intervals
+    /// of all strategies at the stop cover their win rates with probability at
+    /// least `confidence`.
+    ///
+    /// Each round continues where the previous one stopped, so the results are
+    /// the ones [Simulator::play] gives for the number of games played.
+    ///
+    /// ```rust
+    /// use monty_pyrs::{GameConfig, Player, Simulator};
+    ///
+    /// let config = GameConfig::default();
+    /// let simulator = Simulator::builder().seed(1).build().unwrap();
+    /// let (results, games) = simulator.play_until(&config, &Player::CLASSIC, 0.001, 0.95, u64::MAX).unwrap();
+    /// assert_eq!(results.total_games(), games);
+    /// assert_eq!(results, simulator.play(&config, &Player::CLASSIC, games).unwrap());
+    /// for strategy in results.strategies() {
+    ///     let (lower, upper) = results.wilson_interval(&strategy, 0.95).unwrap().unwrap();
+    ///     assert!(upper - lower < 0.002);
+    /// }
+    /// assert!(games < 10_000_000);
+    ///
+    /// // Runs that can't reach the precision stop at the limit
+    /// let (_, games) = simulator.play_until(&config, &Player::CLASSIC, 1e-6, 0.95, 1_000_000).unwrap();
+    /// assert_eq!(games, 1_000_000);
+    /// assert!(simulator.play_until(&config, &Player::CLASSIC, 0., 0.95, 1_000_000).is_err());
+    /// ```
+    pub fn play_until(
+        &self,
+        config: &GameConfig,
+        players: &[Player],
+        precision: f64,
+        confidence: f64,
+        max_iterations: u64,
+    ) -> Result<(Results, u64), MontyError> {
+        self.play_until_cancellable(
+            config,
+            players,
+            precision,
+            confidence,
+            max_iterations,
+            &CancellationToken::new(),
+        )
+    }
+
+    /// Like [Simulator::play_until], but stops early once `token` is cancelled,
+    /// returning the results of the games played so far, marked as incomplete.
+    pub fn play_until_cancellable(
+        &self,
+        config: &GameConfig,
+        players: &[Player],
+        precision: f64,
+        confidence: f64,
+        max_iterations: u64,
+        token: &CancellationToken,
+    ) -> Result<(Results, u64), MontyError> {
+        check_probability(precision, || "the precision".to_string())?;
+        check_probability(confidence, || "the confidence level".to_string())?;
+        check_iterations(players, max_iterations)?;
+        // The level of the check after round `round`, shared between the strategies
+        let level = |round: u64| {
+            1. - (1. - confidence) / (round * (round + 1)) as f64 / players.len() as f64
+        };
+        // Enough games to keep every worker busy
+        let first = CHUNK_SIZE.saturating_mul(self.threads as u64);
+        let mut results = Results::default();
+        let mut played = 0;
+        let mut end = first.min(max_iterations);
+        for round in 1.. {
+            results += self.play_chunks(config, players, played..end, self.seed, token, None);
+            played = end;
+            let mut precise = true;
+            let mut needed: u64 = 0;
+            let z = stats::normal_quantile((1. + level(round + 1)) / 2.);
+            for (_, set) in &results.strategies {
+                let (lower, upper) = stats::wilson_interval(set.wins, set.decided(), level(round));
+                precise &= (upper - lower) / 2. <= precision;
+                // The decided games it takes for an interval as narrow, going by
+                // the win rate and the share of games decided so far
+                let rate = (set.wins as f64 + 1.) / (set.decided() as f64 + 2.);
+                let decided = z * z * rate * (1. - rate) / (precision * precision);
+                let share = (set.decided() as f64 + 1.) / (set.games() as f64 + 1.);
+                needed = needed.max((decided / share * players.len() as f64) as u64);
+            }
+            if precise || played == max_iterations || !results.is_complete() {
+                break;
+            }
+            // Keep rounds on chunk boundaries, and their number logarithmic
+            end = needed
+                .clamp(played.saturating_add(first), played.saturating_mul(4))
+                .div_ceil(CHUNK_SIZE)
+                .saturating_mul(CHUNK_SIZE)
+                .min(max_iterations);
+        }
+        results.runs = vec![RunMetadata::new(
+            config,
+            self.fidelity,
+            Some(self.seed),
+            Some(self.rng),
+            self.threads,
+        )];
+        let games = results.total_games();
+        Ok((results, games))
+    }
+
+    /// Play `iterations` games of every combination of parameters in the
+    /// [Sweep], each with a single strategy switching with the swept probability.
+    ///
+    /// The combinations are played concurrently, so small games keep all of
+    /// the worker threads busy too. Each is seeded from the seed of the
+    /// simulator and its position in the sweep, so that no two play the same
+    /// sequence of games.
+    ///
+    /// ```rust
+    /// use monty_pyrs::{Simulator, Sweep};
+    ///
+    /// let sweep = Sweep::new().switch_probabilities([0., 1.]);
+    /// let results = Simulator::builder().seed(3).build().unwrap().sweep(&sweep, 1000).unwrap();
+    /// let wins = |probability| {
+    ///     let results = results.get(3, 1, probability).unwrap();
+    ///     results.result_sets()[0].1.wins()
+    /// };
+    /// // Had both played the same games, every game lost by staying would have been won by switching
+    /// assert_ne!(wins(0.) + wins(1.), 1000);
+    /// let seed = |probability| results.get(3, 1, probability).unwrap().metadata()[0].seed();
+    /// assert_ne!(seed(0.), seed(1.));
+    /// ```
+    pub fn sweep(&self, sweep: &Sweep, iterations: u64) -> Result<SweepResults, MontyError> {
+        self.sweep_cancellable(sweep, iterations, &CancellationToken::new())
+    }
+
+    /// Like [Simulator::sweep], but stops early once `token` is cancelled.
+    /// Games that were not finished are marked as incomplete.
+    pub fn sweep_cancellable(
+        &self,
+        sweep: &Sweep,
+        iterations: u64,
+        token: &CancellationToken,
+    ) -> Result<SweepResults, MontyError> {
+        let games = sweep.games()?;
+        check_iterations(&[Player::AlwaysSwitch], iterations)?;
+        let points = self.pool.install(|| {
+            games
+                .par_iter()
+                .enumerate()
+                .map(|(index, (point, config))| {
+                    let players = [point.player()];
+                    let seed = rng::stream_seed(self.seed, index as u64);
+                    let results =
+                        self.play_chunks(config, &players, 0..iterations, seed, token, None);
+                    (*point, results)
+                })
+                .collect()
+        });
+        Ok(SweepResults::new(points))
+    }
+
+    /// Play many independent simulations at once, each of its own game and
+    /// number of iterations, with the same strategies. Returns the results in
+    /// the order of `games`.
+    ///
+    /// The simulations are played concurrently, so many small ones keep all of
+    /// the worker threads busy. Each is seeded from the seed of the simulator
+    /// and its position in `games`, so that no two play the same sequence of games.
+    ///
+    /// ```rust
+    /// use monty_pyrs::{GameConfig, Player, Simulator};
+    ///
+    /// let simulator = Simulator::builder().seed(7).build().unwrap();
+    /// let games = vec![(GameConfig::default(), 1000); 100];
+    /// let batch = simulator.play_batch(&games, &Player::CLASSIC).unwrap();
+    /// assert_eq!(batch.len(), 100);
+    /// assert!(batch.iter().all(|results| results.total_games() == 1000));
+    /// assert_ne!(batch[0].result_sets(), batch[1].result_sets());
+    /// ```
+    pub fn play_batch(
+        &self,
+        games: &[(GameConfig, u64)],
+        players: &[Player],
+    ) -> Result<Vec<Results>, MontyError> {
+        self.play_batch_cancellable(games, players, &CancellationToken::new())
+    }
+
+    /// Like [Simulator::play_batch], but stops early once `token` is cancelled.
+    /// Simulations that were not finished are marked as incomplete.
+    pub fn play_batch_cancellable(
+        &self,
+        games: &[(GameConfig, u64)],
+        players: &[Player],
+        token: &CancellationToken,
+    ) -> Result<Vec<Results>, MontyError> {
+        for (_, iterations) in games {
+            check_iterations(players, *iterations)?;
+        }
+        Ok(self.pool.install(|| {
+            games
+                .par_iter()
+                .enumerate()
+                .map(|(index, (config, iterations))| {
+                    let seed = rng::stream_seed(self.seed, index as u64);
+                    self.play_chunks(config, players, 0..*iterations, seed, token, None)
+                })
+                .collect()
+        }))
+    }
+
+    /// Play the games numbered `games` in chunks on the worker pool, with
+    /// generators derived from `seed` and the chunk number, adding the results
+    /// of every finished chunk to `running` if given. Ranges that start and end
+    /// on chunk boundaries can be played one after the other as if in one go.
+    fn play_chunks(
+        &self,
+        config: &GameConfig,
+        players: &[Player],
+        games: Range<u64>,
+        seed: u64,
+        token: &CancellationToken,
+        running: Option<&Mutex<Results>>,
+    ) -> Results {
+        let chunks = games.start / CHUNK_SIZE..games.end.div_ceil(CHUNK_SIZE);
+        let skipped_chunks = AtomicBool::new(false);
+        let mut results = self.pool.install(|| {
+            chunks
+                .into_par_iter()
+                .map(|chunk| {
+                    if token.is_cancelled() {
+                        skipped_chunks.store(true, Ordering::Relaxed);
+                        return None;
+                    }
+                    let start = games.start.max(chunk * CHUNK_SIZE);
+                    let end = games.end.min((chunk + 1) * CHUNK_SIZE);
+                    let seed = rng::stream_seed(seed, chunk);
+                    let results = match self.rng {
+                        RngKind::XorShift => {
+                            self.play_chunk::<XorShiftRng>(config, players, start..end, seed, token)
+                        }
+                        RngKind::Pcg64 => {
+                            self.play_chunk::<Pcg64>(config, players, start..end, seed, token)
+                        }
+                        RngKind::ChaCha8 => {
+                            self.play_chunk::<ChaCha8Rng>(config, players, start..end, seed, token)
+                        }
+                        RngKind::ChaCha20 => {
+                            self.play_chunk::<ChaCha20Rng>(config, players, start..end, seed, token)
+                        }
+                        RngKind::WyRand => {
+                            self.play_chunk::<WyRand>(config, players, start..end, seed, token)
+                        }
+                        RngKind::SplitMix => {
+                            self.play_chunk::<SplitMix64>(config, players, start..end, seed, token)
+                        }
+                    };
+                    if let Some(running) = running {
+                        *running
+                            .lock()
+                            .unwrap_or_else(|poisoned| poisoned.into_inner()) += results.clone();
+                    }
+                    Some(results)
+                })
+                // Stop handing out chunks once one of them saw the cancellation
+                .while_some()
+                .reduce(Results::default, |mut results, chunk| {
+                    results += chunk;
+                    results
+                })
+        });
+        results.cancelled |= skipped_chunks.into_inner();
+        results.runs = vec![RunMetadata::new(
+            config,
+            self.fidelity,
+            Some(seed),
+            Some(self.rng),
+            self.threads,
+        )];
+        results
+    }
+
+    /// Play the games numbered `games` with a generator of type `R` seeded
+    /// with `seed`. Generic rather than using [AnyRng](crate::AnyRng) so that
+    /// every generator gets its own inlined game loop.
+    fn play_chunk<R: RngCore + SeedableRng>(
+        &self,
+        config: &GameConfig,
+        players: &[Player],
+        games: Range<u64>,
+        seed: u64,
+        token: &CancellationToken,
+    ) -> Results {
+        let mut monty = MontyHall::new_with_rng(R::seed_from_u64(seed))
+            .with_fidelity(self.fidelity)
+            .with_cancellation(token.clone());
+        // Checked when the simulator was built
+        monty.backend = self.backend;
+        monty.play_range(config, players, games)
+    }
+}
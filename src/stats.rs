@@ -0,0 +1,383 @@
+//! The statistics behind the confidence intervals and hypothesis tests on
+//! [Results](crate::Results).
+
+/// Relative precision the series and continued fractions are evaluated to.
+const EPSILON: f64 = 1e-15;
+
+/// Guards the continued fractions against division by zero.
+const TINY: f64 = 1e-300;
+
+/// Upper bound on the terms evaluated by the series and continued fractions,
+/// which need roughly the square root of their arguments to converge.
+const MAX_TERMS: u32 = 1 << 20;
+
+/// The natural logarithm of the gamma function for `x > 0`, using the
+/// Lanczos approximation.
+pub(crate) fn ln_gamma(x: f64) -> f64 {
+    const G: f64 = 7.;
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        // Reflection formula, the approximation is only accurate for x >= 0.5
+        return (std::f64::consts::PI / (std::f64::consts::PI * x).sin()).ln() - ln_gamma(1. - x);
+    }
+    let x = x - 1.;
+    let series = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |sum, (i, c)| sum + c / (x + i as f64 + 1.));
+    let t = x + G + 0.5;
+    0.5 * (2. * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + series.ln()
+}
+
+/// The natural logarithm of the binomial coefficient `n` choose `k`.
+fn ln_choose(n: u64, k: u64) -> f64 {
+    ln_gamma(n as f64 + 1.) - ln_gamma(k as f64 + 1.) - ln_gamma((n - k) as f64 + 1.)
+}
+
+/// The regularized upper incomplete gamma function Q(a, x).
+fn gamma_q(a: f64, x: f64) -> f64 {
+    if x <= 0. {
+        return 1.;
+    }
+    let front = (a * x.ln() - x - ln_gamma(a)).exp();
+    if x < a + 1. {
+        // The series for P(a, x) converges quickly here
+        let (mut term, mut sum, mut denominator) = (1. / a, 1. / a, a);
+        for _ in 0..MAX_TERMS {
+            denominator += 1.;
+            term *= x / denominator;
+            sum += term;
+            if term.abs() < sum.abs() * EPSILON {
+                break;
+            }
+        }
+        1. - sum * front
+    } else {
+        // Otherwise use the continued fraction for Q(a, x), evaluated with Lentz's method
+        let mut b = x + 1. - a;
+        let mut c = 1. / TINY;
+        let mut d = 1. / b;
+        let mut h = d;
+        for i in 1..MAX_TERMS {
+            let an = -f64::from(i) * (f64::from(i) - a);
+            b += 2.;
+            d = an * d + b;
+            if d.abs() < TINY {
+                d = TINY;
+            }
+            c = b + an / c;
+            if c.abs() < TINY {
+                c = TINY;
+            }
+            d = 1. / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.).abs() < EPSILON {
+                break;
+            }
+        }
+        front * h
+    }
+}
+
+/// The continued fraction of the incomplete beta function, evaluated with
+/// Lentz's method.
+fn beta_continued_fraction(a: f64, b: f64, x: f64) -> f64 {
+    let clamp = |value: f64| if value.abs() < TINY { TINY } else { value };
+    let mut c = 1.;
+    let mut d = 1. / clamp(1. - (a + b) * x / (a + 1.));
+    let mut h = d;
+    for m in 1..MAX_TERMS {
+        let m = f64::from(m);
+        let even = m * (b - m) * x / ((a - 1. + 2. * m) * (a + 2. * m));
+        d = 1. / clamp(1. + even * d);
+        c = clamp(1. + even / c);
+        h *= d * c;
+        let odd = -(a + m) * (a + b + m) * x / ((a + 2. * m) * (a + 1. + 2. * m));
+        d = 1. / clamp(1. + odd * d);
+        c = clamp(1. + odd / c);
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.).abs() < EPSILON {
+            break;
+        }
+    }
+    h
+}
+
+/// The regularized incomplete beta function I_x(a, b).
+fn beta_cdf(x: f64, a: f64, b: f64) -> f64 {
+    if x <= 0. {
+        return 0.;
+    }
+    if x >= 1. {
+        return 1.;
+    }
+    let ln_beta = ln_gamma(a) + ln_gamma(b) - ln_gamma(a + b);
+    let front = (a * x.ln() + b * (-x).ln_1p() - ln_beta).exp();
+    // The continued fraction converges quickly on one side of the mean, use
+    // the symmetry I_x(a, b) = 1 - I_(1-x)(b, a) on the other
+    if x < (a + 1.) / (a + b + 2.) {
+        front * beta_continued_fraction(a, b, x) / a
+    } else {
+        1. - front * beta_continued_fraction(b, a, 1. - x) / b
+    }
+}
+
+/// The inverse of [beta_cdf] in `x`, found by bisection.
+fn beta_quantile(p: f64, a: f64, b: f64) -> f64 {
+    let (mut low, mut high) = (0f64, 1f64);
+    loop {
+        let middle = (low + high) / 2.;
+        // Stop once the interval can't be halved any further
+        if middle <= low || middle >= high {
+            return middle;
+        }
+        if beta_cdf(middle, a, b) < p {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+}
+
+/// The probability of a standard normal variable being further from zero than `z`.
+pub(crate) fn normal_two_sided_p(z: f64) -> f64 {
+    gamma_q(0.5, z * z / 2.)
+}
+
+/// The cumulative distribution function of the standard normal distribution.
+fn normal_cdf(z: f64) -> f64 {
+    let tail = normal_two_sided_p(z) / 2.;
+    if z < 0. {
+        tail
+    } else {
+        1. - tail
+    }
+}
+
+/// The inverse of [normal_cdf], using Acklam's rational approximation
+/// refined with a step of Halley's method.
+pub(crate) fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [f64; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [f64; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+    const LOW: f64 = 0.02425;
+    let polynomial =
+        |coefficients: &[f64], x: f64| coefficients.iter().fold(0., |sum, c| sum * x + c);
+
+    if p <= 0. {
+        return f64::NEG_INFINITY;
+    }
+    if p >= 1. {
+        return f64::INFINITY;
+    }
+    let tail = |p: f64| {
+        let q = (-2. * p.ln()).sqrt();
+        polynomial(&C, q) / (polynomial(&D, q) * q + 1.)
+    };
+    let x = if p < LOW {
+        tail(p)
+    } else if p > 1. - LOW {
+        -tail(1. - p)
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        polynomial(&A, r) * q / (polynomial(&B, r) * r + 1.)
+    };
+    let error = normal_cdf(x) - p;
+    let u = error * (2. * std::f64::consts::PI).sqrt() * (x * x / 2.).exp();
+    x - u / (1. + x * u / 2.)
+}
+
+/// The survival function of the chi-squared distribution with `degrees` degrees of freedom.
+pub(crate) fn chi_squared_sf(statistic: f64, degrees: u32) -> f64 {
+    gamma_q(f64::from(degrees) / 2., statistic / 2.)
+}
+
+/// The Wilson score interval for `successes` out of `trials` at the given
+/// confidence level.
+pub(crate) fn wilson_interval(successes: u64, trials: u64, level: f64) -> (f64, f64) {
+    if trials == 0 {
+        return (0., 1.);
+    }
+    let n = trials as f64;
+    let p = successes as f64 / n;
+    let z = normal_quantile((1. + level) / 2.);
+    let z2 = z * z;
+    let centre = (p + z2 / (2. * n)) / (1. + z2 / n);
+    let margin = z / (1. + z2 / n) * (p * (1. - p) / n + z2 / (4. * n * n)).sqrt();
+    ((centre - margin).max(0.), (centre + margin).min(1.))
+}
+
+/// The exact Clopper-Pearson interval for `successes` out of `trials` at the
+/// given confidence level.
+pub(crate) fn clopper_pearson_interval(successes: u64, trials: u64, level: f64) -> (f64, f64) {
+    let alpha = 1. - level;
+    let (k, n) = (successes as f64, trials as f64);
+    let lower = match successes {
+        0 => 0.,
+        _ => beta_quantile(alpha / 2., k, n - k + 1.),
+    };
+    let upper = match trials - successes {
+        0 => 1.,
+        _ => beta_quantile(1. - alpha / 2., k + 1., n - k),
+    };
+    (lower, upper)
+}
+
+/// The pooled two-proportion z-test of `first_successes` out of `first_trials`
+/// against `second_successes` out of `second_trials`, returning the z statistic
+/// and the two-sided p-value.
+///
+/// When all trials succeeded or all failed, the proportions are the same and
+/// the statistic is 0.
+pub(crate) fn two_proportion_z_test(
+    first_successes: u64,
+    first_trials: u64,
+    second_successes: u64,
+    second_trials: u64,
+) -> (f64, f64) {
+    let (n1, n2) = (first_trials as f64, second_trials as f64);
+    let pooled = (first_successes + second_successes) as f64 / (n1 + n2);
+    let error = (pooled * (1. - pooled) * (1. / n1 + 1. / n2)).sqrt();
+    if error == 0. {
+        return (0., 1.);
+    }
+    let z = (first_successes as f64 / n1 - second_successes as f64 / n2) / error;
+    (z, normal_two_sided_p(z))
+}
+
+/// Fisher's exact test on the 2x2 table `[[a, b], [c, d]]`, returning the sample
+/// odds ratio and the two-sided p-value.
+///
+/// The p-value sums the probabilities of all tables with the same margins that
+/// are at most as likely as the observed one. Only the tails where those tables
+/// lie are visited, so this stays fast for billions of games.
+///
+/// The odds ratio is infinite when `b * c` is 0 but `a * d` isn't, and 1
+/// when both are 0 because a column is empty, in which case the rows can't
+/// differ.
+pub(crate) fn fisher_exact_test(a: u64, b: u64, c: u64, d: u64) -> (f64, f64) {
+    let (row, other_row, column) = (a + b, c + d, a + c);
+    let total = row + other_row;
+    let (product, cross) = (a as f64 * d as f64, b as f64 * c as f64);
+    let odds_ratio = if cross > 0. {
+        product / cross
+    } else if product > 0. {
+        f64::INFINITY
+    } else {
+        1.
+    };
+    // The top left cell of a table with these margins follows a hypergeometric distribution
+    let ln_pmf =
+        |x: u64| ln_choose(row, x) + ln_choose(other_row, column - x) - ln_choose(total, column);
+    // The ratio of the probabilities of top left cells x + 1 and x
+    let ratio = |x: u64| {
+        ((row - x) as f64 * (column - x) as f64)
+            / ((x + 1) as f64 * (other_row + x + 1 - column) as f64)
+    };
+    let (min, max) = (column.saturating_sub(other_row), row.min(column));
+    let mode =
+        (((row + 1) as f64 * (column + 1) as f64 / (total + 2) as f64) as u64).clamp(min, max);
+
+    let observed = ln_pmf(a);
+    // Allow for rounding errors when comparing with the observed table. They
+    // grow with the log-factorials ln_pmf adds up, the largest of which is
+    // that of the total, bounding ln_choose(total, column) as well
+    let tolerance = (64. * f64::EPSILON * ln_gamma(total as f64 + 1.)).max(1e-7);
+    let threshold = observed + tolerance;
+    if ln_pmf(mode) <= threshold {
+        return (odds_ratio, 1.);
+    }
+
+    // The probabilities increase up to the mode and decrease after it, so
+    // binary search for where each tail starts
+    let mut lower_tail = None;
+    let (mut low, mut high) = (min, mode);
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if ln_pmf(middle) <= threshold {
+            lower_tail = Some(middle);
+            low = middle + 1;
+        } else {
+            high = middle;
+        }
+    }
+    let mut upper_tail = None;
+    let (mut low, mut high) = (mode + 1, max + 1);
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if ln_pmf(middle) <= threshold {
+            upper_tail = Some(middle);
+            high = middle;
+        } else {
+            low = middle + 1;
+        }
+    }
+
+    // Sum the tails relative to the observed probability, walking away from
+    // the mode until the terms no longer matter
+    let mut sum = 0.;
+    if let Some(start) = lower_tail {
+        let mut term = (ln_pmf(start) - observed).exp();
+        let mut x = start;
+        loop {
+            sum += term;
+            if x == min || term < sum * f64::EPSILON {
+                break;
+            }
+            x -= 1;
+            term /= ratio(x);
+        }
+    }
+    if let Some(start) = upper_tail {
+        let mut term = (ln_pmf(start) - observed).exp();
+        let mut x = start;
+        loop {
+            sum += term;
+            if x == max || term < sum * f64::EPSILON {
+                break;
+            }
+            term *= ratio(x);
+            x += 1;
+        }
+    }
+    (odds_ratio, (observed.exp() * sum).min(1.))
+}
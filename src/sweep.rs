@@ -0,0 +1,372 @@
+//! Parameter sweeps, playing every combination of door counts, opened doors
+//! and switch probabilities with [Simulator::sweep](crate::Simulator::sweep).
+
+use crate::{GameConfig, Host, MontyError, Player, PlayerStrategy, Results};
+use num_traits::ToPrimitive;
+use pyo3::exceptions::PyKeyError;
+use pyo3::prelude::*;
+use pyo3::types::{IntoPyDict, PyDict};
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Write};
+
+/// The parameters of a single game in a [Sweep].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SweepPoint {
+    doors: u32,
+    opened_by_host: u32,
+    switch_probability: f64,
+}
+
+impl SweepPoint {
+    /// The total number of doors.
+    pub fn doors(&self) -> u32 {
+        self.doors
+    }
+
+    /// The number of doors the host opens.
+    pub fn opened_by_host(&self) -> u32 {
+        self.opened_by_host
+    }
+
+    /// The probability of the player switching.
+    pub fn switch_probability(&self) -> f64 {
+        self.switch_probability
+    }
+
+    /// The player switching with [SweepPoint::switch_probability], using the
+    /// deterministic strategies when the player always or never switches.
+    pub fn player(&self) -> Player {
+        if self.switch_probability == 1. {
+            Player::AlwaysSwitch
+        } else if self.switch_probability == 0. {
+            Player::AlwaysStay
+        } else {
+            Player::RandomSwitch(self.switch_probability)
+        }
+    }
+}
+
+impl fmt::Display for SweepPoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} doors, {} opened, switching with probability {}",
+            self.doors, self.opened_by_host, self.switch_probability
+        )
+    }
+}
+
+/// The values of each parameter to play every combination of.
+///
+/// Combinations where the host can't open that many doors are skipped, so
+/// wide ranges can be swept without working out which ones are valid.
+///
+/// ```rust
+/// use monty_pyrs::{Simulator, Sweep};
+///
+/// let sweep = Sweep::new()
+///     .doors(3..=6)
+///     .opened_by_host(1..=4)
+///     .switch_probabilities([0., 0.5, 1.]);
+/// let results = Simulator::builder().build().unwrap().sweep(&sweep, 100_000).unwrap();
+/// // 3 doors allow 1 opened door, 4 doors 2, 5 doors 3 and 6 doors 4
+/// assert_eq!(results.len(), (1 + 2 + 3 + 4) * 3);
+///
+/// let switching = results.get(6, 4, 1.).unwrap();
+/// assert!((switching.win_rate("always_switch").unwrap() - 5. / 6.).abs() < 0.01);
+/// assert!(results.to_csv().starts_with("doors,opened_by_host,switch_probability,"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Sweep {
+    doors: Vec<u32>,
+    opened_by_host: Vec<u32>,
+    switch_probabilities: Vec<f64>,
+    host: Host,
+}
+
+/// The classic game, always switching and never switching.
+impl Default for Sweep {
+    fn default() -> Self {
+        Self {
+            doors: vec![3],
+            opened_by_host: vec![1],
+            switch_probabilities: vec![1., 0.],
+            host: Host::default(),
+        }
+    }
+}
+
+impl Sweep {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The door counts to play. Defaults to 3.
+    pub fn doors(mut self, doors: impl IntoIterator<Item = u32>) -> Self {
+        self.doors = doors.into_iter().collect();
+        self
+    }
+
+    /// The numbers of doors opened by the host to play. Defaults to 1.
+    pub fn opened_by_host(mut self, opened_by_host: impl IntoIterator<Item = u32>) -> Self {
+        self.opened_by_host = opened_by_host.into_iter().collect();
+        self
+    }
+
+    /// The probabilities of the player switching to play. Defaults to always
+    /// switching and never switching.
+    pub fn switch_probabilities(mut self, probabilities: impl IntoIterator<Item = f64>) -> Self {
+        self.switch_probabilities = probabilities.into_iter().collect();
+        self
+    }
+
+    /// The host model, the same for every game. Defaults to [Host::Knowledgeable].
+    pub fn host(mut self, host: Host) -> Self {
+        self.host = host;
+        self
+    }
+
+    /// Every valid combination of the parameters along with its game, in the
+    /// order of the parameters given. Fails if a switch probability is not a
+    /// probability or no combination is a valid game.
+    pub(crate) fn games(&self) -> Result<Vec<(SweepPoint, GameConfig)>, MontyError> {
+        if let Some(&p) = self
+            .switch_probabilities
+            .iter()
+            .find(|p| !(0. ..=1.).contains(*p))
+        {
+            return Err(MontyError::InvalidSwitchProbability(p.to_string()));
+        }
+        let mut games = Vec::new();
+        for &doors in &self.doors {
+            for &opened_by_host in &self.opened_by_host {
+                let config = match GameConfig::new(doors, opened_by_host) {
+                    Ok(config) => config.with_host(self.host.clone()),
+                    Err(_) => continue,
+                };
+                for &switch_probability in &self.switch_probabilities {
+                    let point = SweepPoint {
+                        doors,
+                        opened_by_host,
+                        switch_probability,
+                    };
+                    games.push((point, config.clone()));
+                }
+            }
+        }
+        if games.is_empty() {
+            return Err(MontyError::EmptySweep);
+        }
+        Ok(games)
+    }
+}
+
+/// The [Results] of every game in a [Sweep], keyed by its parameters.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[pyclass(module = "monty_pyrs")]
+pub struct SweepResults {
+    points: Vec<(SweepPoint, Results)>,
+}
+
+/// The columns of [SweepResults::to_csv] and of the records passed to pandas.
+const COLUMNS: [&str; 10] = [
+    "doors",
+    "opened_by_host",
+    "switch_probability",
+    "games",
+    "wins",
+    "losses",
+    "voided",
+    "win_rate",
+    "exact_win_rate",
+    "complete",
+];
+
+/// A row of the table, one per game in the sweep.
+struct Record {
+    point: SweepPoint,
+    games: u64,
+    wins: u64,
+    losses: u64,
+    voided: u64,
+    win_rate: Option<f64>,
+    exact_win_rate: Option<f64>,
+    complete: bool,
+}
+
+impl SweepResults {
+    pub(crate) fn new(points: Vec<(SweepPoint, Results)>) -> Self {
+        Self { points }
+    }
+
+    /// The number of games swept.
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// The parameters and results of every game, in the order they were swept.
+    pub fn iter(&self) -> impl Iterator<Item = &(SweepPoint, Results)> {
+        self.points.iter()
+    }
+
+    /// The results of the game with the given parameters, if it was swept.
+    pub fn get(
+        &self,
+        doors: u32,
+        opened_by_host: u32,
+        switch_probability: f64,
+    ) -> Option<&Results> {
+        self.points
+            .iter()
+            .find(|(point, _)| {
+                point.doors == doors
+                    && point.opened_by_host == opened_by_host
+                    && point.switch_probability == switch_probability
+            })
+            .map(|(_, results)| results)
+    }
+
+    fn records(&self) -> Vec<Record> {
+        self.points
+            .iter()
+            .map(|(point, results)| {
+                let strategy = point.player().name();
+                let set = results.result_set(&strategy).unwrap_or_default();
+                Record {
+                    point: *point,
+                    games: set.games(),
+                    wins: set.wins(),
+                    losses: set.losses(),
+                    voided: set.voided(),
+                    win_rate: set.win_rate(),
+                    exact_win_rate: results
+                        .exact_win_rate(&strategy)
+                        .and_then(|rate| rate.to_f64()),
+                    complete: results.is_complete(),
+                }
+            })
+            .collect()
+    }
+
+    /// The table as CSV with a header row, one row per game. Undefined win
+    /// rates are left empty.
+    pub fn to_csv(&self) -> String {
+        let rate = |rate: Option<f64>| rate.map_or_else(String::new, |rate| rate.to_string());
+        let mut csv = COLUMNS.join(",");
+        csv.push('\n');
+        for record in self.records() {
+            // Writing to a String can't fail
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{},{},{},{},{},{}",
+                record.point.doors,
+                record.point.opened_by_host,
+                record.point.switch_probability,
+                record.games,
+                record.wins,
+                record.losses,
+                record.voided,
+                rate(record.win_rate),
+                rate(record.exact_win_rate),
+                record.complete
+            );
+        }
+        csv
+    }
+
+    /// Save the results as JSON, including how every game was played.
+    pub fn to_json(&self) -> Result<String, MontyError> {
+        serde_json::to_string(self).map_err(|err| MontyError::Serialization(err.to_string()))
+    }
+}
+
+#[pymethods]
+impl SweepResults {
+    fn __len__(&self) -> usize {
+        self.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("SweepResults({} games)", self.len())
+    }
+
+    /// The results of the game keyed by `(doors, opened_by_host, switch_probability)`.
+    fn __getitem__(&self, key: (u32, u32, f64)) -> PyResult<Results> {
+        let (doors, opened_by_host, switch_probability) = key;
+        self.get(doors, opened_by_host, switch_probability)
+            .cloned()
+            .ok_or_else(|| PyKeyError::new_err((key,)))
+    }
+
+    /// The parameters and results of every game as a list of
+    /// `((doors, opened_by_host, switch_probability), Results)` tuples.
+    fn items(&self) -> Vec<((u32, u32, f64), Results)> {
+        self.points
+            .iter()
+            .map(|(point, results)| {
+                let key = (point.doors, point.opened_by_host, point.switch_probability);
+                (key, results.clone())
+            })
+            .collect()
+    }
+
+    /// The table as a list of dicts, one per game.
+    fn to_records<'py>(&self, py: Python<'py>) -> PyResult<Vec<&'py PyDict>> {
+        self.records()
+            .into_iter()
+            .map(|record| {
+                let dict = PyDict::new(py);
+                dict.set_item(COLUMNS[0], record.point.doors)?;
+                dict.set_item(COLUMNS[1], record.point.opened_by_host)?;
+                dict.set_item(COLUMNS[2], record.point.switch_probability)?;
+                dict.set_item(COLUMNS[3], record.games)?;
+                dict.set_item(COLUMNS[4], record.wins)?;
+                dict.set_item(COLUMNS[5], record.losses)?;
+                dict.set_item(COLUMNS[6], record.voided)?;
+                dict.set_item(COLUMNS[7], record.win_rate)?;
+                dict.set_item(COLUMNS[8], record.exact_win_rate)?;
+                dict.set_item(COLUMNS[9], record.complete)?;
+                Ok(dict)
+            })
+            .collect()
+    }
+
+    /// The table as a `pandas.DataFrame` with one row per game. Requires pandas.
+    fn to_pandas(&self, py: Python) -> PyResult<PyObject> {
+        let data_frame = py.import("pandas")?.getattr("DataFrame")?;
+        let columns = [("columns", COLUMNS.to_vec())].into_py_dict(py);
+        Ok(data_frame
+            .call_method("from_records", (self.to_records(py)?,), Some(columns))?
+            .into())
+    }
+
+    /// Python's [SweepResults::to_csv]. Writes the CSV to `path` if given,
+    /// otherwise returns it.
+    #[pyo3(name = "to_csv")]
+    #[args(path = "None")]
+    fn py_to_csv(&self, path: Option<String>) -> PyResult<Option<String>> {
+        match path {
+            Some(path) => {
+                std::fs::write(path, self.to_csv())?;
+                Ok(None)
+            }
+            None => Ok(Some(self.to_csv())),
+        }
+    }
+
+    /// Python's [SweepResults::to_json].
+    #[pyo3(name = "to_json")]
+    fn py_to_json(&self) -> Result<String, MontyError> {
+        self.to_json()
+    }
+
+    /// Load results saved with [SweepResults::to_json].
+    #[staticmethod]
+    pub fn from_json(json: &str) -> Result<Self, MontyError> {
+        serde_json::from_str(json).map_err(|err| MontyError::Serialization(err.to_string()))
+    }
+}
@@ -0,0 +1,245 @@
+//! Records of individual games, for showing how games play out and for
+//! checking that the hosts stick to their rules.
+
+use crate::{AnyRng, GameConfig, HostAction, MontyHall, Outcome, Player};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use rand_core::RngCore;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Range;
+
+/// The course of a single game, as played by [MontyHall::play_traced].
+///
+/// ```rust
+/// use monty_pyrs::{Fidelity, GameConfig, HostAction, MontyHall, Outcome, Player};
+///
+/// let mut monty = MontyHall::default().with_fidelity(Fidelity::Faithful);
+/// let config = GameConfig::new(5, 3).unwrap();
+/// for record in monty.trace(&config, &Player::CLASSIC, 1000).unwrap() {
+///     // The knowledgeable host never reveals the car or opens the chosen door
+///     assert!(!record.host_revealed_car());
+///     assert!(!record.opened().contains(&record.initial_choice()));
+///     assert_eq!(record.opened().len(), 3);
+///     assert_eq!(record.host_action(), HostAction::Offer);
+///     assert_eq!(record.switched(), record.strategy() == "always_switch");
+///     assert_eq!(record.outcome() == Outcome::Won, record.final_choice() == record.car());
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[pyclass(module = "monty_pyrs")]
+pub struct GameRecord {
+    pub(crate) strategy: String,
+    pub(crate) car: u32,
+    pub(crate) initial_choice: u32,
+    pub(crate) opened: Vec<u32>,
+    pub(crate) host_action: HostAction,
+    pub(crate) switched: bool,
+    pub(crate) final_choice: u32,
+    pub(crate) outcome: Outcome,
+}
+
+impl GameRecord {
+    /// The doors opened by the host, in the order they were opened.
+    pub fn opened(&self) -> &[u32] {
+        &self.opened
+    }
+
+    /// What the host did after opening the doors.
+    pub fn host_action(&self) -> HostAction {
+        self.host_action
+    }
+
+    /// Whether the game was won, lost or voided.
+    pub fn outcome(&self) -> Outcome {
+        self.outcome
+    }
+}
+
+impl fmt::Display for GameRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let opened: Vec<String> = self.opened.iter().map(u32::to_string).collect();
+        write!(
+            f,
+            "{}: car behind {}, picked {}, host opened [{}], ",
+            self.strategy,
+            self.car,
+            self.initial_choice,
+            opened.join(", ")
+        )?;
+        match self.host_action {
+            HostAction::Void => write!(f, "voided"),
+            _ if self.switched => write!(f, "switched to {}, {}", self.final_choice, self.outcome),
+            _ => write!(f, "stayed, {}", self.outcome),
+        }
+    }
+}
+
+#[pymethods]
+impl GameRecord {
+    /// The name of the strategy that played the game.
+    #[getter]
+    pub fn strategy(&self) -> &str {
+        &self.strategy
+    }
+
+    /// The door hiding the car.
+    #[getter]
+    pub fn car(&self) -> u32 {
+        self.car
+    }
+
+    /// The door picked by the player before the host stepped in.
+    #[getter]
+    pub fn initial_choice(&self) -> u32 {
+        self.initial_choice
+    }
+
+    /// Whether the player switched doors.
+    #[getter]
+    pub fn switched(&self) -> bool {
+        self.switched
+    }
+
+    /// The door the player ended up with.
+    #[getter]
+    pub fn final_choice(&self) -> u32 {
+        self.final_choice
+    }
+
+    /// Whether one of the doors opened by the host hid the car.
+    #[getter]
+    pub fn host_revealed_car(&self) -> bool {
+        self.opened.contains(&self.car)
+    }
+
+    #[getter(opened)]
+    fn py_opened(&self) -> Vec<u32> {
+        self.opened.clone()
+    }
+
+    /// `"offer"`, `"no_offer"` or `"void"`.
+    #[getter(host_action)]
+    fn py_host_action(&self) -> String {
+        self.host_action.to_string()
+    }
+
+    /// `"won"`, `"lost"` or `"voided"`.
+    #[getter(outcome)]
+    fn py_outcome(&self) -> String {
+        self.outcome.to_string()
+    }
+
+    /// The record as a dict, eg. to build a `pandas.DataFrame` from.
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let dict = PyDict::new(py);
+        dict.set_item("strategy", &self.strategy)?;
+        dict.set_item("car", self.car)?;
+        dict.set_item("initial_choice", self.initial_choice)?;
+        dict.set_item("opened", &self.opened)?;
+        dict.set_item("host_action", self.host_action.to_string())?;
+        dict.set_item("switched", self.switched)?;
+        dict.set_item("final_choice", self.final_choice)?;
+        dict.set_item("outcome", self.outcome.to_string())?;
+        Ok(dict)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "GameRecord(strategy={:?}, car={}, initial_choice={}, opened={:?}, host_action={:?}, switched={}, final_choice={}, outcome={:?})",
+            self.strategy,
+            self.car,
+            self.initial_choice,
+            self.opened,
+            self.host_action.to_string(),
+            if self.switched { "True" } else { "False" },
+            self.final_choice,
+            self.outcome.to_string()
+        )
+    }
+
+    fn __str__(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// An iterator over the records of the games played by [MontyHall::trace].
+pub struct Trace<'a, R> {
+    monty: &'a mut MontyHall<R>,
+    config: &'a GameConfig,
+    players: &'a [Player],
+    games: Range<u64>,
+}
+
+impl<'a, R> Trace<'a, R> {
+    pub(crate) fn new(
+        monty: &'a mut MontyHall<R>,
+        config: &'a GameConfig,
+        players: &'a [Player],
+        iterations: u64,
+    ) -> Self {
+        Self {
+            monty,
+            config,
+            players,
+            games: 0..iterations,
+        }
+    }
+}
+
+impl<R: RngCore> Iterator for Trace<'_, R> {
+    type Item = GameRecord;
+
+    fn next(&mut self) -> Option<GameRecord> {
+        let game = self.games.next()?;
+        let player = &self.players[(game % self.players.len() as u64) as usize];
+        Some(self.monty.play_traced(self.config, player))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.games.size_hint()
+    }
+}
+
+/// The Python iterator over traced games, owning what [Trace] borrows.
+#[pyclass(module = "monty_pyrs")]
+pub(crate) struct GameTrace {
+    monty: MontyHall<AnyRng>,
+    config: GameConfig,
+    players: Vec<Player>,
+    games: Range<u64>,
+}
+
+impl GameTrace {
+    pub(crate) fn new(
+        monty: MontyHall<AnyRng>,
+        config: GameConfig,
+        players: Vec<Player>,
+        iterations: u64,
+    ) -> Self {
+        Self {
+            monty,
+            config,
+            players,
+            games: 0..iterations,
+        }
+    }
+}
+
+#[pymethods]
+impl GameTrace {
+    fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> Option<GameRecord> {
+        let game = self.games.next()?;
+        let player = &self.players[(game % self.players.len() as u64) as usize];
+        Some(self.monty.play_traced(&self.config, player))
+    }
+
+    /// The number of games left to play.
+    fn __length_hint__(&self) -> usize {
+        self.games.size_hint().0
+    }
+}
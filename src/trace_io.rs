@@ -0,0 +1,447 @@
+//! Streaming [GameRecord]s to files and replaying the files to recompute the
+//! [Results], for traces too large to hold in memory.
+//!
+//! Three formats are supported:
+//!
+//! - CSV, one row per game with the opened doors separated by spaces
+//! - Newline-delimited JSON, one [GameRecord] per line
+//! - A bit-packed binary format keeping only the strategy, whether the
+//!   player switched and the outcome of each game, which takes 4 bits per
+//!   game when playing two strategies
+//!
+//! The binary format starts with the magic bytes `MONTYTRC`, a version byte
+//! and the strategy names, each prefixed with its length as a little-endian
+//! `u16` after the `u16` number of names. The games follow in blocks of a
+//! little-endian `u32` number of games and the packed games, least significant
+//! bits first, ending with an empty block. Each game is packed as the index of
+//! its strategy, in as few bits as fit all indices, followed by 2 bits for the
+//! outcome (won, lost or voided) and 1 bit for whether the player switched.
+//!
+//! ```rust
+//! use monty_pyrs::{replay, Fidelity, GameConfig, MontyHall, Player, TraceFormat, TraceWriter};
+//!
+//! let config = GameConfig::default();
+//! let mut monty = MontyHall::default().with_fidelity(Fidelity::Faithful);
+//! let mut writer = TraceWriter::new(Vec::new(), TraceFormat::Binary, &Player::CLASSIC).unwrap();
+//! for record in monty.trace(&config, &Player::CLASSIC, 100_000).unwrap() {
+//!     writer.write(&record).unwrap();
+//! }
+//! let bytes = writer.finish().unwrap();
+//! assert!(bytes.len() < 100_000 / 2 + 100);
+//!
+//! // The same games as played without tracing
+//! let mut monty = MontyHall::default().with_fidelity(Fidelity::Faithful);
+//! let results = monty.play_multiple(&config, &Player::CLASSIC, 100_000).unwrap();
+//! assert_eq!(replay(&bytes[..]).unwrap().result_sets(), results.result_sets());
+//! ```
+
+use crate::{GameRecord, HostAction, MontyError, Outcome, Player, PlayerStrategy, Results};
+use derive_more::Display;
+use std::convert::TryFrom;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::str::FromStr;
+
+/// Starts every file in the binary format.
+const MAGIC: &[u8; 8] = b"MONTYTRC";
+
+/// The version of the binary format, bumped on incompatible changes.
+const VERSION: u8 = 1;
+
+/// The number of games buffered before writing a block of the binary format.
+const BLOCK_GAMES: u32 = 1 << 16;
+
+/// The header row of the CSV format.
+const CSV_HEADER: &str =
+    "strategy,car,initial_choice,opened,host_action,switched,final_choice,outcome";
+
+/// The file formats of [TraceWriter].
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq)]
+pub enum TraceFormat {
+    #[display(fmt = "csv")]
+    Csv,
+    /// Newline-delimited JSON
+    #[display(fmt = "ndjson")]
+    Ndjson,
+    /// Bit-packed, keeping only what's needed to recompute the [Results]
+    #[display(fmt = "binary")]
+    Binary,
+}
+
+/// Parses the names printed by [Display](std::fmt::Display).
+impl FromStr for TraceFormat {
+    type Err = MontyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(TraceFormat::Csv),
+            "ndjson" => Ok(TraceFormat::Ndjson),
+            "binary" => Ok(TraceFormat::Binary),
+            _ => Err(MontyError::UnknownTraceFormat(s.to_string())),
+        }
+    }
+}
+
+fn io_error(err: io::Error) -> MontyError {
+    MontyError::Trace(err.to_string())
+}
+
+/// Writes game records to `W` as they are played.
+///
+/// Writes are not buffered, so wrap files in a [BufWriter](std::io::BufWriter).
+/// The trace is only complete once [TraceWriter::finish] is called.
+pub struct TraceWriter<W: Write> {
+    inner: W,
+    format: TraceFormat,
+    strategies: Vec<String>,
+    games: u64,
+    packer: Packer,
+}
+
+impl<W: Write> TraceWriter<W> {
+    /// Start a trace of games played by `players`, writing the header if the
+    /// format has one. Only games of these players can be written.
+    pub fn new(mut inner: W, format: TraceFormat, players: &[Player]) -> Result<Self, MontyError> {
+        let strategies: Vec<String> = players.iter().map(|player| player.name()).collect();
+        match format {
+            TraceFormat::Csv => writeln!(inner, "{}", CSV_HEADER).map_err(io_error)?,
+            TraceFormat::Ndjson => {}
+            TraceFormat::Binary => {
+                let count = u16::try_from(strategies.len())
+                    .map_err(|_| MontyError::Trace("too many strategies".to_string()))?;
+                inner.write_all(MAGIC).map_err(io_error)?;
+                inner.write_all(&[VERSION]).map_err(io_error)?;
+                inner.write_all(&count.to_le_bytes()).map_err(io_error)?;
+                for name in &strategies {
+                    let len = u16::try_from(name.len()).map_err(|_| {
+                        MontyError::Trace(format!("strategy name too long: {}", name))
+                    })?;
+                    inner.write_all(&len.to_le_bytes()).map_err(io_error)?;
+                    inner.write_all(name.as_bytes()).map_err(io_error)?;
+                }
+            }
+        }
+        Ok(Self {
+            inner,
+            format,
+            packer: Packer::new(strategy_bits(strategies.len())),
+            strategies,
+            games: 0,
+        })
+    }
+
+    /// The number of games written so far.
+    pub fn games(&self) -> u64 {
+        self.games
+    }
+
+    /// Append a game to the trace. Fails if it was played by a strategy the
+    /// writer wasn't created with, or the game can't be written.
+    pub fn write(&mut self, record: &GameRecord) -> Result<(), MontyError> {
+        let strategy = self
+            .strategies
+            .iter()
+            .position(|name| *name == record.strategy)
+            .ok_or_else(|| MontyError::Trace(format!("unexpected strategy {}", record.strategy)))?;
+        match self.format {
+            TraceFormat::Csv => {
+                let opened: Vec<String> = record.opened.iter().map(u32::to_string).collect();
+                writeln!(
+                    self.inner,
+                    "{},{},{},{},{},{},{},{}",
+                    csv_field(&record.strategy),
+                    record.car,
+                    record.initial_choice,
+                    opened.join(" "),
+                    record.host_action,
+                    record.switched,
+                    record.final_choice,
+                    record.outcome
+                )
+                .map_err(io_error)?;
+            }
+            TraceFormat::Ndjson => {
+                serde_json::to_writer(&mut self.inner, record)
+                    .map_err(|err| MontyError::Trace(err.to_string()))?;
+                self.inner.write_all(b"\n").map_err(io_error)?;
+            }
+            TraceFormat::Binary => {
+                self.packer
+                    .push(strategy as u64, record.outcome, record.switched);
+                if self.packer.games == BLOCK_GAMES {
+                    self.packer.write_block(&mut self.inner)?;
+                }
+            }
+        }
+        self.games += 1;
+        Ok(())
+    }
+
+    /// Complete the trace, returning the underlying writer after flushing it.
+    pub fn finish(mut self) -> Result<W, MontyError> {
+        if self.format == TraceFormat::Binary {
+            if self.packer.games > 0 {
+                self.packer.write_block(&mut self.inner)?;
+            }
+            // An empty block marks the end of the trace
+            self.inner
+                .write_all(&0u32.to_le_bytes())
+                .map_err(io_error)?;
+        }
+        self.inner.flush().map_err(io_error)?;
+        Ok(self.inner)
+    }
+}
+
+/// Quote a CSV field if it contains a separator or quote, as done for the
+/// strategy names of CSV traces.
+///
+/// ```rust
+/// use monty_pyrs::csv_field;
+///
+/// assert_eq!(csv_field("always_switch"), "always_switch");
+/// assert_eq!(csv_field("pick \"one\", then two"), "\"pick \"\"one\"\", then two\"");
+/// ```
+pub fn csv_field(field: &str) -> String {
+    if field.contains([',', '"']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// The number of bits needed to tell `strategies` strategies apart.
+fn strategy_bits(strategies: usize) -> u32 {
+    usize::BITS - strategies.saturating_sub(1).leading_zeros()
+}
+
+fn outcome_code(outcome: Outcome) -> u64 {
+    match outcome {
+        Outcome::Won => 0,
+        Outcome::Lost => 1,
+        Outcome::Voided => 2,
+    }
+}
+
+/// Packs games of the binary format into a block.
+struct Packer {
+    strategy_bits: u32,
+    bytes: Vec<u8>,
+    /// Bits not yet making up a whole byte, lowest first
+    pending: u64,
+    pending_bits: u32,
+    games: u32,
+}
+
+impl Packer {
+    fn new(strategy_bits: u32) -> Self {
+        Self {
+            strategy_bits,
+            bytes: Vec::new(),
+            pending: 0,
+            pending_bits: 0,
+            games: 0,
+        }
+    }
+
+    fn push(&mut self, strategy: u64, outcome: Outcome, switched: bool) {
+        let code = strategy
+            | outcome_code(outcome) << self.strategy_bits
+            | (switched as u64) << (self.strategy_bits + 2);
+        self.pending |= code << self.pending_bits;
+        self.pending_bits += self.strategy_bits + 3;
+        while self.pending_bits >= 8 {
+            self.bytes.push(self.pending as u8);
+            self.pending >>= 8;
+            self.pending_bits -= 8;
+        }
+        self.games += 1;
+    }
+
+    fn write_block<W: Write>(&mut self, inner: &mut W) -> Result<(), MontyError> {
+        if self.pending_bits > 0 {
+            self.bytes.push(self.pending as u8);
+        }
+        inner
+            .write_all(&self.games.to_le_bytes())
+            .map_err(io_error)?;
+        inner.write_all(&self.bytes).map_err(io_error)?;
+        self.bytes.clear();
+        self.pending = 0;
+        self.pending_bits = 0;
+        self.games = 0;
+        Ok(())
+    }
+}
+
+/// Read a trace written by [TraceWriter] in any of the formats, telling them
+/// apart by their first bytes, and add up the games into [Results].
+///
+/// The results have no [RunMetadata](crate::RunMetadata), as the traces don't
+/// record how the games were played. Fails if the trace is malformed or was
+/// not finished.
+pub fn replay<R: Read>(reader: R) -> Result<Results, MontyError> {
+    let mut reader = BufReader::new(reader);
+    let start = reader.fill_buf().map_err(io_error)?;
+    let format = if start.starts_with(MAGIC) {
+        TraceFormat::Binary
+    } else if start.starts_with(b"{") {
+        TraceFormat::Ndjson
+    } else if start.starts_with(CSV_HEADER.as_bytes()) {
+        TraceFormat::Csv
+    } else if start.is_empty() {
+        return Err(MontyError::Trace("the trace is empty".to_string()));
+    } else {
+        return Err(MontyError::Trace("not a trace".to_string()));
+    };
+    match format {
+        TraceFormat::Csv => replay_lines(reader.lines().skip(1), 2, parse_csv),
+        TraceFormat::Ndjson => replay_lines(reader.lines(), 1, |line| {
+            serde_json::from_str(line).map_err(|err| err.to_string())
+        }),
+        TraceFormat::Binary => replay_binary(reader),
+    }
+}
+
+fn add(results: &mut Results, strategy: String, outcome: Outcome) {
+    let index = results.index_of(strategy);
+    results.strategies[index].1.record(outcome);
+}
+
+/// Add up the games of a text format, one per line, with line numbers
+/// starting at `first_line` for error messages.
+fn replay_lines<I, F>(lines: I, first_line: usize, parse: F) -> Result<Results, MontyError>
+where
+    I: Iterator<Item = io::Result<String>>,
+    F: Fn(&str) -> Result<GameRecord, String>,
+{
+    let mut results = Results::default();
+    for (number, line) in lines.enumerate() {
+        let line = line.map_err(io_error)?;
+        if line.is_empty() {
+            continue;
+        }
+        let record = parse(&line)
+            .map_err(|err| MontyError::Trace(format!("line {}: {}", number + first_line, err)))?;
+        add(&mut results, record.strategy, record.outcome);
+    }
+    Ok(results)
+}
+
+fn parse_csv(line: &str) -> Result<GameRecord, String> {
+    let (strategy, rest) = match line.strip_prefix('"') {
+        Some(quoted) => {
+            // Doubled quotes are escaped quotes, so the field ends at the first
+            // quote followed by the separator
+            let end = quoted
+                .find("\",")
+                .ok_or_else(|| "unterminated quote".to_string())?;
+            (quoted[..end].replace("\"\"", "\""), &quoted[end + 2..])
+        }
+        None => {
+            let (strategy, rest) = line
+                .split_once(',')
+                .ok_or_else(|| "missing columns".to_string())?;
+            (strategy.to_string(), rest)
+        }
+    };
+    let fields: Vec<&str> = rest.split(',').collect();
+    let (car, initial_choice, opened, host_action, switched, final_choice, outcome) = match fields[..]
+    {
+        [a, b, c, d, e, f, g] => (a, b, c, d, e, f, g),
+        _ => return Err(format!("expected 8 columns, not {}", fields.len() + 1)),
+    };
+    let door = |field: &str| {
+        field
+            .parse::<u32>()
+            .map_err(|_| format!("{} is not a door", field))
+    };
+    Ok(GameRecord {
+        strategy,
+        car: door(car)?,
+        initial_choice: door(initial_choice)?,
+        opened: opened
+            .split_whitespace()
+            .map(door)
+            .collect::<Result<_, _>>()?,
+        host_action: match host_action {
+            "offer" => HostAction::Offer,
+            "no_offer" => HostAction::NoOffer,
+            "void" => HostAction::Void,
+            _ => return Err(format!("unknown host action {}", host_action)),
+        },
+        switched: switched
+            .parse()
+            .map_err(|_| format!("{} is not true or false", switched))?,
+        final_choice: door(final_choice)?,
+        outcome: match outcome {
+            "won" => Outcome::Won,
+            "lost" => Outcome::Lost,
+            "voided" => Outcome::Voided,
+            _ => return Err(format!("unknown outcome {}", outcome)),
+        },
+    })
+}
+
+fn replay_binary<R: Read>(mut reader: R) -> Result<Results, MontyError> {
+    let truncated = |err: io::Error| match err.kind() {
+        io::ErrorKind::UnexpectedEof => {
+            MontyError::Trace("the trace is truncated, or was not finished".to_string())
+        }
+        _ => io_error(err),
+    };
+    let mut header = [0; MAGIC.len() + 3];
+    reader.read_exact(&mut header).map_err(truncated)?;
+    if header[MAGIC.len()] != VERSION {
+        return Err(MontyError::Trace(format!(
+            "unsupported version {} of the binary format",
+            header[MAGIC.len()]
+        )));
+    }
+    let count = u16::from_le_bytes([header[MAGIC.len() + 1], header[MAGIC.len() + 2]]) as usize;
+    let mut results = Results::default();
+    let mut indices = Vec::with_capacity(count);
+    for _ in 0..count {
+        let mut len = [0; 2];
+        reader.read_exact(&mut len).map_err(truncated)?;
+        let mut name = vec![0; u16::from_le_bytes(len) as usize];
+        reader.read_exact(&mut name).map_err(truncated)?;
+        let name = String::from_utf8(name)
+            .map_err(|_| MontyError::Trace("strategy name is not UTF-8".to_string()))?;
+        indices.push(results.index_of(name));
+    }
+
+    let strategy_bits = strategy_bits(count);
+    let game_bits = strategy_bits + 3;
+    let mut block = Vec::new();
+    loop {
+        let mut games = [0; 4];
+        reader.read_exact(&mut games).map_err(truncated)?;
+        let games = u32::from_le_bytes(games) as u64;
+        if games == 0 {
+            return Ok(results);
+        }
+        block.resize((games * game_bits as u64).div_ceil(8) as usize, 0);
+        reader.read_exact(&mut block).map_err(truncated)?;
+        let (mut bytes, mut pending, mut pending_bits) = (block.iter(), 0u64, 0);
+        for _ in 0..games {
+            while pending_bits < game_bits {
+                // The block holds enough bytes for all of its games
+                pending |= (*bytes.next().unwrap_or(&0) as u64) << pending_bits;
+                pending_bits += 8;
+            }
+            let strategy = (pending & ((1 << strategy_bits) - 1)) as usize;
+            let outcome = match (pending >> strategy_bits) & 0b11 {
+                0 => Outcome::Won,
+                1 => Outcome::Lost,
+                2 => Outcome::Voided,
+                _ => return Err(MontyError::Trace("invalid outcome".to_string())),
+            };
+            pending >>= game_bits;
+            pending_bits -= game_bits;
+            let index = *indices
+                .get(strategy)
+                .ok_or_else(|| MontyError::Trace("invalid strategy".to_string()))?;
+            results.strategies[index].1.record(outcome);
+        }
+    }
+}